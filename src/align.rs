@@ -0,0 +1,129 @@
+use core::ops::Range;
+
+use error::{self, Result};
+use pread::Offset;
+
+/// The range of padding from `offset` up to the next multiple of `align`, checked against `len`
+pub(crate) fn padding<I: Offset>(len: usize, offset: I, align: usize) -> Result<Range<usize>> {
+    let offset = offset.try_into_index()?;
+    if align == 0 {
+        return Err(error::Error::BadInput { range: offset..offset, size: len, msg: "alignment must be non-zero" })
+    }
+    let end = error::check_range(offset, (align - offset % align) % align, len)?;
+    Ok(offset..end)
+}
+
+/// Skips or writes the padding used by many binary formats to align records, and fills byte ranges
+///
+/// The `g` methods update their offset like [Gread](trait.Gread.html) and [Gwrite](trait.Gwrite.html), and the `p` methods don't.
+/// None of these read or write a value, so unlike those traits there is no context or error type to pick,
+/// and any failure, e.g., a zero alignment, or padding past the end of the bytes, is a scroll `Error`; the offset is only updated on success.
+///
+/// Implemented for every `AsRef<[u8]>`; the writing methods additionally need `AsMut<[u8]>`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Align, Gread, Gwrite, LE};
+/// let mut bytes = [0xffu8; 8];
+/// let offset = &mut 0;
+/// bytes.gwrite_with(1u16, offset, LE).unwrap();
+/// bytes.gwrite_align(offset, 4, 0).unwrap();
+/// bytes.gwrite_with(2u32, offset, LE).unwrap();
+/// assert_eq!(bytes, [1, 0, 0, 0, 2, 0, 0, 0]);
+/// let offset = &mut 0;
+/// assert_eq!(bytes.gread_with::<u16>(offset, LE).unwrap(), 1);
+/// bytes.gread_align_zeroed(offset, 4).unwrap();
+/// assert_eq!(bytes.gread_with::<u32>(offset, LE).unwrap(), 2);
+/// ```
+pub trait Align<I: Offset = usize>: AsRef<[u8]> {
+    /// Advances `offset` to the next multiple of `align`, skipping any padding without reading it.
+    ///
+    /// An `offset` which is already aligned doesn't move.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Align, Gread, LE};
+    /// // a 3 byte name, padded to 4, followed by a u32
+    /// let bytes = [b'a', b'b', b'c', 0xff, 1, 0, 0, 0];
+    /// let offset = &mut 0;
+    /// let name: &str = bytes.gread_slice(offset, 3).unwrap();
+    /// bytes.gread_align(offset, 4).unwrap();
+    /// assert_eq!(*offset, 4);
+    /// assert_eq!(bytes.gread_with::<u32>(offset, LE).unwrap(), 1);
+    /// bytes.gread_align(offset, 4).unwrap();
+    /// assert_eq!(*offset, 8);
+    /// assert!(bytes.gread_align(&mut 7, 16).is_err());
+    /// ```
+    #[inline]
+    fn gread_align(&self, offset: &mut I, align: usize) -> Result<()> {
+        let range = padding(self.as_ref().len(), *offset, align)?;
+        *offset = I::try_from_index(range.end)?;
+        Ok(())
+    }
+    /// Advances `offset` to the next multiple of `align` like [gread_align](#method.gread_align), but also checks that the skipped padding is all zeroes.
+    ///
+    /// Non-zero padding is a `BadInput` error over the padding's range.
+    /// # Example
+    /// ```rust
+    /// use scroll::Align;
+    /// let bytes = [b'a', b'b', b'c', 0, b'd', 0xff, 0, 0];
+    /// let offset = &mut 3;
+    /// bytes.gread_align_zeroed(offset, 4).unwrap();
+    /// assert_eq!(*offset, 4);
+    /// let offset = &mut 6;
+    /// bytes.gread_align_zeroed(offset, 8).unwrap();
+    /// let offset = &mut 5;
+    /// assert!(bytes.gread_align_zeroed(offset, 8).is_err());
+    /// assert_eq!(*offset, 5);
+    /// ```
+    #[inline]
+    fn gread_align_zeroed(&self, offset: &mut I, align: usize) -> Result<()> {
+        let bytes = self.as_ref();
+        let range = padding(bytes.len(), *offset, align)?;
+        if bytes[range.clone()].iter().any(|&byte| byte != 0) {
+            return Err(error::Error::BadInput { range, size: bytes.len(), msg: "padding is not zero" })
+        }
+        *offset = I::try_from_index(range.end)?;
+        Ok(())
+    }
+    /// Pads `self` with `fill` from `offset` up to the next multiple of `align`, and updates the offset.
+    ///
+    /// An `offset` which is already aligned doesn't move. Nothing is written if the padding doesn't fit.
+    #[inline]
+    fn gwrite_align(&mut self, offset: &mut I, align: usize, fill: u8) -> Result<()>
+        where Self: AsMut<[u8]> {
+        let bytes = self.as_mut();
+        let range = padding(bytes.len(), *offset, align)?;
+        let end = I::try_from_index(range.end)?;
+        bytes[range].fill(fill);
+        *offset = end;
+        Ok(())
+    }
+    /// Writes `count` copies of `fill` into `self` at `offset`, e.g., to blank out a reserved field
+    /// # Example
+    /// ```
+    /// use scroll::Align;
+    /// let mut bytes = [0u8; 6];
+    /// bytes.pwrite_fill(0xff, 1, 4).unwrap();
+    /// assert_eq!(bytes, [0, 0xff, 0xff, 0xff, 0xff, 0]);
+    /// bytes.pwrite_zeroes(2, 2).unwrap();
+    /// assert_eq!(bytes, [0, 0xff, 0, 0, 0xff, 0]);
+    /// assert!(bytes.pwrite_zeroes(4, 3).is_err());
+    /// ```
+    #[inline]
+    fn pwrite_fill(&mut self, fill: u8, offset: I, count: usize) -> Result<()>
+        where Self: AsMut<[u8]> {
+        let bytes = self.as_mut();
+        let offset = offset.try_into_index()?;
+        let end = error::check_range(offset, count, bytes.len())?;
+        bytes[offset..end].fill(fill);
+        Ok(())
+    }
+    /// Writes `count` zeroes into `self` at `offset`
+    #[inline]
+    fn pwrite_zeroes(&mut self, offset: I, count: usize) -> Result<()>
+        where Self: AsMut<[u8]> {
+        self.pwrite_fill(0, offset, count)
+    }
+}
+
+impl<T: ?Sized + AsRef<[u8]>> Align for T {}
@@ -0,0 +1,273 @@
+use core::any::type_name;
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{Write, Seek, SeekFrom};
+
+use ctx::{SizeWith, TryIntoCtx};
+#[cfg(feature = "std")]
+use ctx::{self, TryIntoStream};
+use endian::Endian;
+use error;
+use pread::Offset;
+
+/// A placeholder for an `N`, reserved by [Reservations](struct.Reservations.html), which must be filled in before they are finished
+///
+/// A slot is only marked filled once a fill succeeds, so a failed fill can be retried; filling it again writes the new value over the old one.
+/// It remembers the context it was reserved with, so it's written the same way, and the `Reservations` it was reserved by, which are the only ones that can fill it.
+#[must_use = "a reserved slot must be filled"]
+#[derive(Debug)]
+pub struct Slot<N, Ctx = Endian> {
+    offset: u64,
+    ctx: Ctx,
+    index: usize,
+    owner: usize,
+    marker: PhantomData<N>,
+}
+
+impl<N, Ctx> Slot<N, Ctx> {
+    /// The offset of the slot in the buffer, or stream, it was reserved in
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+/// Backpatching for headers whose sizes and offsets are only known after what follows them has been written
+///
+/// `reserve` writes zeroes in place of an `N` at the current `gwrite` offset and hands back a typed [Slot](struct.Slot.html);
+/// once the value is known, `fill` writes it into the slot, and `fill_distance` writes the distance from the start of the slot to a later offset.
+/// `finish` then errors with `Unfilled` if any slot was forgotten. Filling a slot reserved by some other `Reservations` is a `BadInput` error, and writes nothing.
+///
+/// The `_stream` variants do the same on any `Write + Seek` sink at its current position, e.g., a `File`, or a `Cursor<Vec<u8>>` for a buffer which grows as it's written;
+/// filling seeks back to the slot, and then returns to where the stream was. Only present when `alloc` feature is used (the default).
+///
+/// # Example
+/// ```rust
+/// use scroll::{Reservations, Gwrite, Pread, LE};
+/// let mut bytes = [0u8; 16];
+/// let mut reservations = Reservations::new();
+/// let offset = &mut 0;
+/// bytes.gwrite_with(0xfeu8, offset, LE).unwrap();
+/// let size = reservations.reserve::<u16, _, _>(&mut bytes, offset, LE).unwrap();
+/// let payload = reservations.reserve::<u32, _, _>(&mut bytes, offset, LE).unwrap();
+/// bytes.gwrite_with(0xdeadbeefu32, offset, LE).unwrap();
+/// reservations.fill_distance(&mut bytes, &size, *offset).unwrap();
+/// reservations.fill(&mut bytes, &payload, 7).unwrap();
+/// reservations.finish().unwrap();
+/// assert_eq!(bytes.pread_with::<u16>(1, LE).unwrap(), 10);
+/// assert_eq!(bytes.pread_with::<u32>(3, LE).unwrap(), 7);
+/// ```
+#[derive(Debug)]
+pub struct Reservations {
+    // tells our slots apart from those of other reservations
+    id: usize,
+    // the offset of every slot handed out, by index, or `None` once filled
+    pending: Vec<Option<u64>>,
+}
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The distance from `start` to `end`, as an `N`
+fn distance<N: TryFrom<u64>>(start: u64, end: u64) -> error::Result<N> {
+    let distance = end.checked_sub(start).ok_or_else(|| error::Error::BadOffset(usize::try_from(end).unwrap_or(usize::MAX)))?;
+    N::try_from(distance).map_err(|_| error::Error::BadOffsetType { offset: i128::from(distance), ty: type_name::<N>() })
+}
+
+impl Default for Reservations {
+    fn default() -> Self {
+        Reservations::new()
+    }
+}
+
+impl Reservations {
+    /// Creates a new set of reservations, with no slots
+    pub fn new() -> Self {
+        Reservations { id: NEXT_ID.fetch_add(1, Ordering::Relaxed), pending: Vec::new() }
+    }
+    /// The number of slots which haven't been filled yet
+    pub fn unfilled(&self) -> usize {
+        self.pending.iter().filter(|slot| slot.is_some()).count()
+    }
+    fn track<N, Ctx>(&mut self, offset: u64, ctx: Ctx) -> Slot<N, Ctx> {
+        let index = self.pending.len();
+        self.pending.push(Some(offset));
+        Slot { offset, ctx, index, owner: self.id, marker: PhantomData }
+    }
+    fn check<N, Ctx>(&self, slot: &Slot<N, Ctx>) -> error::Result<()> {
+        if slot.owner != self.id {
+            let offset = usize::try_from(slot.offset).unwrap_or(usize::MAX);
+            return Err(error::Error::BadInput { range: offset..offset, size: 0, msg: "slot was reserved by other reservations" })
+        }
+        Ok(())
+    }
+    fn untrack<N, Ctx>(&mut self, slot: &Slot<N, Ctx>) {
+        if let Some(pending) = self.pending.get_mut(slot.index) {
+            *pending = None;
+        }
+    }
+    /// Reserves a slot for an `N` written with `ctx` at `offset` in `dst`, zeroing it, and advances the offset past it
+    pub fn reserve<N, Ctx, W>(&mut self, dst: &mut W, offset: &mut usize, ctx: Ctx) -> error::Result<Slot<N, Ctx>>
+        where N: SizeWith<Ctx, Units = usize>,
+              Ctx: Copy,
+              W: ?Sized + AsMut<[u8]> {
+        let dst = dst.as_mut();
+        let o = *offset;
+        let end = error::check_range(o, N::size_with(&ctx), dst.len())?;
+        dst[o..end].fill(0);
+        *offset = end;
+        Ok(self.track(o as u64, ctx))
+    }
+    /// Writes `n` into `slot` in `dst`, and marks it filled if that succeeded
+    pub fn fill<N, Ctx, W>(&mut self, dst: &mut W, slot: &Slot<N, Ctx>, n: N) -> error::Result<()>
+        where N: TryIntoCtx<(usize, Ctx), Error = error::Error>,
+              Ctx: Copy,
+              W: ?Sized + AsMut<[u8]> {
+        self.check(slot)?;
+        n.try_into_ctx(dst.as_mut(), (slot.offset.try_into_index()?, slot.ctx))?;
+        self.untrack(slot);
+        Ok(())
+    }
+    /// Writes the distance from the start of `slot` to `offset`, e.g., the size of everything written since it was reserved, into `slot` in `dst`
+    ///
+    /// Errors if `offset` is before the slot, or if the distance doesn't fit in an `N`.
+    pub fn fill_distance<N, Ctx, W>(&mut self, dst: &mut W, slot: &Slot<N, Ctx>, offset: usize) -> error::Result<()>
+        where N: TryIntoCtx<(usize, Ctx), Error = error::Error> + TryFrom<u64>,
+              Ctx: Copy,
+              W: ?Sized + AsMut<[u8]> {
+        let n = distance(slot.offset, offset as u64)?;
+        self.fill(dst, slot, n)
+    }
+    /// Reserves a slot for an `N` written with `ctx` at the current position of `dst`, by writing zeroes in its place
+    #[cfg(feature = "std")]
+    pub fn reserve_stream<N, Ctx, W>(&mut self, dst: &mut W, ctx: Ctx) -> error::Result<Slot<N, Ctx>>
+        where N: SizeWith<Ctx, Units = usize>,
+              Ctx: Copy,
+              W: ?Sized + Write + Seek {
+        let offset = dst.stream_position()?;
+        ctx::with_scratch(N::size_with(&ctx), |buf| dst.write_all(buf))?;
+        Ok(self.track(offset, ctx))
+    }
+    /// Seeks back to `slot` in `dst`, writes `n` into it, and returns to the current position; the slot is marked filled if all of that succeeded
+    #[cfg(feature = "std")]
+    pub fn fill_stream<N, Ctx, W>(&mut self, dst: &mut W, slot: &Slot<N, Ctx>, n: N) -> error::Result<()>
+        where N: TryIntoStream<Ctx, Error = error::Error>,
+              Ctx: Copy,
+              W: ?Sized + Write + Seek {
+        self.check(slot)?;
+        let position = dst.stream_position()?;
+        dst.seek(SeekFrom::Start(slot.offset))?;
+        let res = n.try_into_stream(dst, slot.ctx);
+        dst.seek(SeekFrom::Start(position))?;
+        res?;
+        self.untrack(slot);
+        Ok(())
+    }
+    /// Writes the distance from the start of `slot` to the current position of `dst` into `slot`
+    #[cfg(feature = "std")]
+    pub fn fill_distance_stream<N, Ctx, W>(&mut self, dst: &mut W, slot: &Slot<N, Ctx>) -> error::Result<()>
+        where N: TryIntoStream<Ctx, Error = error::Error> + TryFrom<u64>,
+              Ctx: Copy,
+              W: ?Sized + Write + Seek {
+        let n = distance(slot.offset, dst.stream_position()?)?;
+        self.fill_stream(dst, slot, n)
+    }
+    /// Checks that every reserved slot was filled, or errors with `Unfilled`
+    pub fn finish(self) -> error::Result<()> {
+        let mut unfilled = self.pending.iter().filter_map(|&slot| slot);
+        match unfilled.next() {
+            Some(offset) => Err(error::Error::Unfilled { offset, count: 1 + unfilled.count() }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reservations;
+    use super::super::{Pread, Gwrite, Lwrite, Error, LE, BE};
+    use std::io::Cursor;
+
+    #[test]
+    fn memory() {
+        let mut bytes = vec![0xffu8; 12];
+        let mut reservations = Reservations::new();
+        let offset = &mut 2;
+        let count = reservations.reserve::<u16, _, _>(&mut bytes, offset, BE).unwrap();
+        let end = reservations.reserve::<u32, _, _>(&mut bytes, offset, LE).unwrap();
+        assert_eq!((count.offset(), end.offset()), (2, 4));
+        assert_eq!(bytes[2..8], [0; 6]);
+        assert_eq!(reservations.unfilled(), 2);
+        bytes.gwrite_with(0xabu8, offset, LE).unwrap();
+        reservations.fill(&mut bytes, &count, 1).unwrap();
+        reservations.fill_distance(&mut bytes, &end, *offset).unwrap();
+        assert_eq!(reservations.unfilled(), 0);
+        reservations.finish().unwrap();
+        assert_eq!(bytes.pread_with::<u16>(2, BE).unwrap(), 1);
+        assert_eq!(bytes.pread_with::<u32>(4, LE).unwrap(), 5);
+        // no room for the slot
+        let mut reservations = Reservations::new();
+        assert!(reservations.reserve::<u64, _, _>(&mut bytes, &mut 8, LE).is_err());
+        assert_eq!(bytes[8..], [0xab, 0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut bytes = [0u8; 300];
+        let mut reservations = Reservations::new();
+        let offset = &mut 0;
+        let small = reservations.reserve::<u8, _, _>(&mut bytes, offset, LE).unwrap();
+        let _a = reservations.reserve::<u16, _, _>(&mut bytes, offset, LE).unwrap();
+        let _b = reservations.reserve::<u16, _, _>(&mut bytes, offset, LE).unwrap();
+        match reservations.fill_distance(&mut bytes, &small, 300) {
+            Err(Error::BadOffsetType { offset: 300, ty: "u8" }) => (),
+            res => panic!("expected a BadOffsetType, got {:?}", res),
+        }
+        let early = reservations.reserve::<u32, _, _>(&mut bytes, offset, LE).unwrap();
+        assert!(reservations.fill_distance(&mut bytes, &early, 0).is_err());
+        assert!(reservations.fill(&mut [0u8; 4], &early, 1).is_err());
+        assert_eq!(reservations.unfilled(), 4);
+        // failed fills can be retried
+        reservations.fill_distance(&mut bytes, &small, 255).unwrap();
+        reservations.fill_distance(&mut bytes, &early, 300).unwrap();
+        assert_eq!((bytes[0], bytes.pread_with::<u32>(5, LE).unwrap()), (255, 295));
+        match reservations.finish() {
+            Err(Error::Unfilled { offset: 1, count: 2 }) => (),
+            res => panic!("expected Unfilled, got {:?}", res),
+        }
+        // a slot can only be filled by the reservations it came from
+        let mut bytes = [0u8; 4];
+        let mut a = Reservations::new();
+        let mut b = Reservations::default();
+        let foreign = a.reserve::<u16, _, _>(&mut bytes, &mut 0, LE).unwrap();
+        let _own = b.reserve::<u16, _, _>(&mut bytes, &mut 2, LE).unwrap();
+        match b.fill(&mut bytes, &foreign, 0xffff) {
+            Err(Error::BadInput { .. }) => (),
+            res => panic!("expected a BadInput, got {:?}", res),
+        }
+        assert_eq!(bytes, [0; 4]);
+        assert_eq!(b.unfilled(), 1);
+        assert!(b.finish().is_err());
+    }
+
+    #[test]
+    fn stream() {
+        let mut cursor = Cursor::new(Vec::new());
+        let mut reservations = Reservations::new();
+        cursor.lwrite_with(0xcafebabeu32, BE).unwrap();
+        let length = reservations.reserve_stream::<u64, _, _>(&mut cursor, LE).unwrap();
+        let kind = reservations.reserve_stream::<u8, _, _>(&mut cursor, LE).unwrap();
+        cursor.lwrite_with(1u16, LE).unwrap();
+        reservations.fill_stream(&mut cursor, &kind, 9).unwrap();
+        reservations.fill_distance_stream(&mut cursor, &length).unwrap();
+        cursor.lwrite_with(2u16, LE).unwrap();
+        reservations.finish().unwrap();
+        let bytes = cursor.into_inner();
+        assert_eq!(bytes.len(), 17);
+        assert_eq!(bytes.pread_with::<u64>(4, LE).unwrap(), 11);
+        assert_eq!(bytes[12], 9);
+        assert_eq!(bytes.pread_with::<u16>(15, LE).unwrap(), 2);
+    }
+}
@@ -0,0 +1,412 @@
+use core::convert::{AsRef, AsMut};
+
+use error;
+
+/// The order in which the bits of each byte are consumed by a `BitCursor`
+#[derive(PartialEq, Eq, Copy, Debug, Clone)]
+pub enum BitOrder {
+    /// The most significant bit of each byte comes first, and the first bit read is the most significant bit of the value, e.g., H.264, MPEG audio
+    MsbFirst,
+    /// The least significant bit of each byte comes first, and the first bit read is the least significant bit of the value, e.g., DEFLATE
+    LsbFirst,
+}
+
+/// Most significant bit first order
+pub const MSB: BitOrder = BitOrder::MsbFirst;
+/// Least significant bit first order
+pub const LSB: BitOrder = BitOrder::LsbFirst;
+
+impl Default for BitOrder {
+    #[inline]
+    fn default() -> Self {
+        MSB
+    }
+}
+
+/// A cursor which reads and writes values a bit at a time, for packed bitfields in codec headers, compressed streams, or hardware registers.
+///
+/// Values are 1 to 64 bits wide, and can be unsigned, signed (two's complement), or Exp-Golomb coded.
+/// The cursor's position is in **bits**, and so are the `range` and `size` of any `Error::BadRange` it returns, e.g., reading 4 bits at bit 14 of a 2 byte buffer
+/// errors with `BadRange { range: 14..18, size: 16 }`.
+///
+/// To go back to reading whole bytes, `align` the cursor, and continue from the byte offset it returns with `Pread` or `Gread`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{BitCursor, Gread, MSB, BE};
+/// // an MPEG audio frame header: a 11 bit sync word, 2 bit version, 2 bit layer, then the protection bit
+/// let bytes = [0xff, 0xfb, 0x90, 0x64, 0x00, 0x2a];
+/// let mut bits = BitCursor::new(&bytes[..], MSB);
+/// assert_eq!(bits.read(11).unwrap(), 0x7ff);
+/// assert_eq!(bits.read(2).unwrap(), 0b11);
+/// assert_eq!(bits.read(2).unwrap(), 0b01);
+/// assert!(bits.read_bit().unwrap());
+/// assert_eq!(bits.read_signed(4).unwrap(), -7);
+/// let offset = &mut bits.align();
+/// assert_eq!(*offset, 3);
+/// let rest: u16 = bytes.gread_with(offset, BE).unwrap();
+/// assert_eq!(rest, 0x6400);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitCursor<T> {
+    inner: T,
+    position: usize,
+    order: BitOrder,
+}
+
+impl<T> BitCursor<T> {
+    /// Creates a new cursor at the start of `inner`, consuming bits in `order`
+    #[inline]
+    pub fn new(inner: T, order: BitOrder) -> Self {
+        BitCursor { inner, position: 0, order }
+    }
+    /// Creates a new cursor at the byte `offset` into `inner`, e.g., where a `Gread` left off
+    #[inline]
+    pub fn with_offset(inner: T, offset: usize, order: BitOrder) -> Self {
+        BitCursor { inner, position: offset.saturating_mul(8), order }
+    }
+    /// The current position, in bits
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    /// Sets the current position, in bits
+    #[inline]
+    pub fn set_position(&mut self, position: usize) {
+        self.position = position;
+    }
+    #[inline]
+    pub fn order(&self) -> BitOrder {
+        self.order
+    }
+    /// Whether the cursor is on a byte boundary
+    #[inline]
+    pub fn is_aligned(&self) -> bool {
+        self.position & 7 == 0
+    }
+    /// Skips any remaining bits of the current byte, and returns the byte offset of the next one, to continue reading or writing bytes from
+    #[inline]
+    pub fn align(&mut self) -> usize {
+        let offset = self.position.div_ceil(8);
+        self.position = offset.saturating_mul(8);
+        offset
+    }
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+    /// Consumes self and returns the inner buffer
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+    #[inline]
+    fn mask(&self, bit: usize) -> u8 {
+        match self.order {
+            MSB => 0x80 >> (bit % 8),
+            LSB => 1 << (bit % 8),
+        }
+    }
+}
+
+#[inline]
+fn check_bits(position: usize, bits: u32, len: usize) -> error::Result<()> {
+    if bits == 0 || bits > 64 {
+        Err(error::Error::BadInput { range: position..position.saturating_add(bits as usize), size: len.saturating_mul(8), msg: "bit width must be between 1 and 64" })
+    } else {
+        Ok(())
+    }
+}
+
+#[inline]
+fn check_range(position: usize, bits: u32, len: usize) -> error::Result<()> {
+    error::check_range(position, bits as usize, len.saturating_mul(8)).map(|_| ())
+}
+
+impl<T: AsRef<[u8]>> BitCursor<T> {
+    #[inline]
+    fn bit_at(&self, position: usize) -> bool {
+        self.inner.as_ref()[position / 8] & self.mask(position) != 0
+    }
+    /// Reads `bits` bits, in any width including 0, after they've been bounds checked
+    #[inline]
+    fn read_unchecked(&mut self, bits: u32) -> u64 {
+        let mut value = 0;
+        for i in 0..bits {
+            let bit = self.bit_at(self.position) as u64;
+            match self.order {
+                MSB => value = (value << 1) | bit,
+                LSB => value |= bit << i,
+            }
+            self.position += 1;
+        }
+        value
+    }
+    /// Reads an unsigned value `bits` wide, which must be between 1 and 64
+    #[inline]
+    pub fn read(&mut self, bits: u32) -> error::Result<u64> {
+        let len = self.inner.as_ref().len();
+        check_bits(self.position, bits, len)?;
+        check_range(self.position, bits, len)?;
+        Ok(self.read_unchecked(bits))
+    }
+    /// Reads a two's complement signed value `bits` wide, which must be between 1 and 64
+    #[inline]
+    pub fn read_signed(&mut self, bits: u32) -> error::Result<i64> {
+        let value = self.read(bits)?;
+        let shift = 64 - bits;
+        Ok(((value << shift) as i64) >> shift)
+    }
+    /// Reads a single bit
+    #[inline]
+    pub fn read_bit(&mut self) -> error::Result<bool> {
+        Ok(self.read(1)? == 1)
+    }
+    /// Reads an unsigned Exp-Golomb code, i.e., an H.264 `ue(v)`
+    pub fn read_exp_golomb(&mut self) -> error::Result<u64> {
+        let start = self.position;
+        let size = self.inner.as_ref().len().saturating_mul(8);
+        let mut position = start;
+        loop {
+            if position >= size {
+                return Err(error::Error::BadRange { range: start..position.saturating_add(1), size })
+            }
+            if self.bit_at(position) {
+                break
+            }
+            position += 1;
+            if position - start > 63 {
+                return Err(error::Error::BadInput { range: start..position, size, msg: "exp-golomb code is too long for 64 bits" })
+            }
+        }
+        let zeros = (position - start) as u32;
+        let end = position.saturating_add(1 + zeros as usize);
+        if end > size {
+            return Err(error::Error::BadRange { range: start..end, size })
+        }
+        self.position = position + 1;
+        Ok(((1u64 << zeros) - 1) + self.read_unchecked(zeros))
+    }
+    /// Reads a signed Exp-Golomb code, i.e., an H.264 `se(v)`
+    #[inline]
+    pub fn read_signed_exp_golomb(&mut self) -> error::Result<i64> {
+        let k = self.read_exp_golomb()?;
+        if k % 2 == 1 {
+            Ok(k.div_ceil(2) as i64)
+        } else {
+            Ok(-((k / 2) as i64))
+        }
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> BitCursor<T> {
+    /// Writes the low `bits` of `value`, after they've been bounds checked
+    #[inline]
+    fn write_unchecked(&mut self, value: u64, bits: u32) {
+        for i in 0..bits {
+            let bit = match self.order {
+                MSB => (value >> (bits - 1 - i)) & 1,
+                LSB => (value >> i) & 1,
+            };
+            let mask = self.mask(self.position);
+            let byte = &mut self.inner.as_mut()[self.position / 8];
+            if bit == 1 { *byte |= mask } else { *byte &= !mask }
+            self.position += 1;
+        }
+    }
+    /// Writes `value` as an unsigned value `bits` wide, which must be between 1 and 64; errors if `value` doesn't fit
+    #[inline]
+    pub fn write(&mut self, value: u64, bits: u32) -> error::Result<()> {
+        let len = self.inner.as_ref().len();
+        check_bits(self.position, bits, len)?;
+        check_range(self.position, bits, len)?;
+        if bits < 64 && value >> bits != 0 {
+            return Err(error::Error::BadInput { range: self.position..self.position.saturating_add(bits as usize), size: len.saturating_mul(8), msg: "value does not fit in bit width" })
+        }
+        self.write_unchecked(value, bits);
+        Ok(())
+    }
+    /// Writes `value` as a two's complement signed value `bits` wide, which must be between 1 and 64; errors if `value` doesn't fit
+    #[inline]
+    pub fn write_signed(&mut self, value: i64, bits: u32) -> error::Result<()> {
+        let len = self.inner.as_ref().len();
+        check_bits(self.position, bits, len)?;
+        check_range(self.position, bits, len)?;
+        let shift = 64 - bits;
+        if (value << shift) >> shift != value {
+            return Err(error::Error::BadInput { range: self.position..self.position.saturating_add(bits as usize), size: len.saturating_mul(8), msg: "value does not fit in bit width" })
+        }
+        self.write_unchecked(value as u64, bits);
+        Ok(())
+    }
+    /// Writes a single bit
+    #[inline]
+    pub fn write_bit(&mut self, bit: bool) -> error::Result<()> {
+        self.write(bit as u64, 1)
+    }
+    /// Writes `value` as an unsigned Exp-Golomb code, i.e., an H.264 `ue(v)`; `u64::MAX` can't be encoded
+    pub fn write_exp_golomb(&mut self, value: u64) -> error::Result<()> {
+        let len = self.inner.as_ref().len();
+        let code = match value.checked_add(1) {
+            Some(code) => code,
+            None => return Err(error::Error::BadInput { range: self.position..self.position, size: len.saturating_mul(8), msg: "value is too large for a 64 bit exp-golomb code" }),
+        };
+        let bits = 64 - code.leading_zeros();
+        check_range(self.position, 2 * bits - 1, len)?;
+        self.write_unchecked(0, bits - 1);
+        self.write_unchecked(code, bits);
+        Ok(())
+    }
+    /// Writes `value` as a signed Exp-Golomb code, i.e., an H.264 `se(v)`; `i64::MIN` can't be encoded
+    #[inline]
+    pub fn write_signed_exp_golomb(&mut self, value: i64) -> error::Result<()> {
+        let code = if value > 0 {
+            Some(value as u64 * 2 - 1)
+        } else {
+            value.unsigned_abs().checked_mul(2)
+        };
+        match code {
+            Some(code) => self.write_exp_golomb(code),
+            None => Err(error::Error::BadInput { range: self.position..self.position, size: self.inner.as_ref().len().saturating_mul(8), msg: "value is too large for a 64 bit exp-golomb code" }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitCursor, MSB, LSB};
+    use super::super::Error;
+
+    #[test]
+    fn msb_first() {
+        let bytes = [0b1010_1100, 0b0011_1111];
+        let mut bits = BitCursor::new(&bytes, MSB);
+        assert_eq!(bits.read(3).unwrap(), 0b101);
+        assert_eq!(bits.read(7).unwrap(), 0b0_1100_00);
+        assert_eq!(bits.read_signed(6).unwrap(), -1);
+        assert_eq!(bits.position(), 16);
+        match bits.read(1) {
+            Err(Error::BadRange { range, size }) => { assert_eq!(range, 16..17); assert_eq!(size, 16) },
+            res => panic!("expected a BadRange, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn lsb_first() {
+        // DEFLATE: BFINAL = 1, BTYPE = 01
+        let bytes = [0b0000_0011, 0xff];
+        let mut bits = BitCursor::new(&bytes, LSB);
+        assert!(bits.read_bit().unwrap());
+        assert_eq!(bits.read(2).unwrap(), 0b01);
+        assert_eq!(bits.read(13).unwrap(), 0b1_1111_1110_0000);
+        assert_eq!(bits.align(), 2);
+    }
+
+    #[test]
+    fn widths() {
+        let bytes = [0xffu8; 9];
+        let mut bits = BitCursor::new(&bytes, MSB);
+        assert!(bits.read(0).is_err());
+        assert!(bits.read(65).is_err());
+        assert_eq!(bits.read(1).unwrap(), 1);
+        assert_eq!(bits.read(64).unwrap(), u64::MAX);
+        assert_eq!(bits.read_signed(7).unwrap(), -1);
+        let mut bits = BitCursor::with_offset(&bytes, 1, LSB);
+        assert_eq!(bits.read_signed(64).unwrap(), -1);
+        assert!(bits.read(1).is_err());
+    }
+
+    #[test]
+    fn write_read_roundtrip() {
+        for &order in [MSB, LSB].iter() {
+            let mut bits = BitCursor::new([0u8; 24], order);
+            bits.write(0b101, 3).unwrap();
+            bits.write_signed(-300, 13).unwrap();
+            bits.write_bit(true).unwrap();
+            bits.write(u64::MAX, 64).unwrap();
+            bits.write_signed(i64::MIN, 64).unwrap();
+            assert!(bits.write(4, 2).is_err());
+            assert!(bits.write_signed(2, 2).is_err());
+            assert!(bits.write_signed(-3, 2).is_err());
+            bits.write_signed(-2, 2).unwrap();
+            let end = bits.position();
+            assert!(bits.write(0, 192 - end as u32 + 1).is_err());
+            let mut bits = BitCursor::new(bits.into_inner(), order);
+            assert_eq!(bits.read(3).unwrap(), 0b101);
+            assert_eq!(bits.read_signed(13).unwrap(), -300);
+            assert!(bits.read_bit().unwrap());
+            assert_eq!(bits.read(64).unwrap(), u64::MAX);
+            assert_eq!(bits.read_signed(64).unwrap(), i64::MIN);
+            assert_eq!(bits.read_signed(2).unwrap(), -2);
+            assert_eq!(bits.position(), end);
+        }
+    }
+
+    #[test]
+    fn write_preserves_other_bits() {
+        let mut bytes = [0xffu8, 0xff];
+        {
+            let mut bits = BitCursor::with_offset(&mut bytes[..], 0, MSB);
+            bits.set_position(6);
+            bits.write(0, 4).unwrap();
+        }
+        assert_eq!(bytes, [0b1111_1100, 0b0011_1111]);
+    }
+
+    #[test]
+    fn exp_golomb() {
+        // ue(v) 0..=4: 1, 010, 011, 00100, 00101
+        let bytes = [0b1010_0110, 0b0100_0010, 0b1000_0000];
+        let mut bits = BitCursor::new(&bytes, MSB);
+        for i in 0..5 {
+            assert_eq!(bits.read_exp_golomb().unwrap(), i);
+        }
+        let mut bits = BitCursor::new(&bytes, MSB);
+        for &i in [0, 1, -1, 2, -2].iter() {
+            assert_eq!(bits.read_signed_exp_golomb().unwrap(), i);
+        }
+        let mut bits = BitCursor::new([0u8; 128], MSB);
+        for &i in [0, 7, u64::MAX - 1, 1 << 40].iter() {
+            bits.write_exp_golomb(i).unwrap();
+        }
+        for &i in [0, -7, 7, i64::MAX, -i64::MAX].iter() {
+            bits.write_signed_exp_golomb(i).unwrap();
+        }
+        assert!(bits.write_exp_golomb(u64::MAX).is_err());
+        assert!(bits.write_signed_exp_golomb(i64::MIN).is_err());
+        let mut bits = BitCursor::new(bits.into_inner(), MSB);
+        for &i in [0, 7, u64::MAX - 1, 1 << 40].iter() {
+            assert_eq!(bits.read_exp_golomb().unwrap(), i);
+        }
+        for &i in [0, -7, 7, i64::MAX, -i64::MAX].iter() {
+            assert_eq!(bits.read_signed_exp_golomb().unwrap(), i);
+        }
+    }
+
+    #[test]
+    fn exp_golomb_errors() {
+        // runs out in the leading zeros, and then in the suffix
+        let mut bits = BitCursor::new([0u8; 1], MSB);
+        match bits.read_exp_golomb() {
+            Err(Error::BadRange { range, size }) => { assert_eq!(range, 0..9); assert_eq!(size, 8) },
+            res => panic!("expected a BadRange, got {:?}", res),
+        }
+        let mut bits = BitCursor::new([0b0000_0010], MSB);
+        match bits.read_exp_golomb() {
+            Err(Error::BadRange { range, .. }) => assert_eq!(range, 0..13),
+            res => panic!("expected a BadRange, got {:?}", res),
+        }
+        assert_eq!(bits.position(), 0);
+        assert!(BitCursor::new([0u8; 9], MSB).read_exp_golomb().is_err());
+        let mut bits = BitCursor::new([0u8; 1], MSB);
+        assert!(bits.write_exp_golomb(15).is_err());
+        assert_eq!(bits.position(), 0);
+        // a position at the very end of the address space is a range error, not an overflow
+        bits.set_position(usize::MAX);
+        match bits.read_exp_golomb() {
+            Err(Error::BadRange { range, size: 8 }) => assert_eq!(range, usize::MAX..usize::MAX),
+            res => panic!("expected a BadRange, got {:?}", res),
+        }
+    }
+}
@@ -1,25 +1,47 @@
 use core::convert::From;
 use core::ops::{Deref, DerefMut};
 
-use std::io::{self, Read, Write};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write, Seek, SeekFrom};
+#[cfg(feature = "std")]
+use core::convert::TryFrom;
 
 /// A byte buffer which is versed in both the Greater and Lesser arts
 ///
-/// Convenient for grabbing all the data from a file, and then using `Pread`/`Pwrite`, etc., on it. Only present when `std` feature is used (the default).
+/// Convenient for grabbing all the data from a file, and then using `Pread`/`Pwrite`, etc., on it. Only present when `alloc` feature is used (the default); the `Read`, `Write` and `Seek` impls need `std`.
 ///
 /// # Example
 /// ```rust
 /// use scroll::{ctx, Pread, Pwrite, Buffer};
 /// let hello_world: &str = "hello world";
-/// let mut buffer = Buffer::with(0x0u8, hello_world.len());
+/// // leave room for the null terminator
+/// let mut buffer = Buffer::with(0x0u8, hello_world.len() + 1);
 /// buffer.pwrite(hello_world, 0).unwrap();
 /// let hello = buffer.pread_with::<&str>(0, ctx::SPACE).unwrap();
 /// assert_eq!(hello, "hello");
 /// ```
-
+///
+/// A `Buffer` also has a position, like a `std::io::Cursor<Vec<u8>>`, which is advanced by its `Read` and `Write` impls and
+/// can be moved with `Seek`. The position has no effect on `Pread`/`Pwrite`, etc., which always use the offset they are given.
+/// Writes past the end of the buffer grow it.
+///
+/// ```rust
+/// use scroll::{Buffer, Lread, Lwrite, BE};
+/// use std::io::{Seek, SeekFrom};
+/// let mut buffer = Buffer::default();
+/// buffer.lwrite_with(0xdeadbeefu32, BE).unwrap();
+/// buffer.lwrite_with(0x7fu8, BE).unwrap();
+/// assert_eq!(buffer.len(), 5);
+/// buffer.seek(SeekFrom::Start(0)).unwrap();
+/// assert_eq!(buffer.lread_with::<u32>(BE).unwrap(), 0xdeadbeef);
+/// assert_eq!(buffer.lread::<u8>().unwrap(), 0x7f);
+/// assert!(buffer.lread::<u8>().is_err());
+/// ```
 #[derive(Default, Debug)]
 pub struct Buffer {
-    inner: Vec<u8>
+    inner: Vec<u8>,
+    position: usize,
 }
 
 impl Buffer {
@@ -30,7 +52,7 @@ impl Buffer {
     /// let bytes: [u8; 2] = [0x48, 0x49];
     /// let buffer = Buffer::new(bytes);
     pub fn new<T: AsRef<[u8]>> (bytes: T) -> Self {
-        Buffer { inner: Vec::from(bytes.as_ref()) }
+        Buffer { inner: Vec::from(bytes.as_ref()), position: 0 }
     }
     /// Initializes a new buffer with `seed`, `size` times
     /// # Example
@@ -38,7 +60,7 @@ impl Buffer {
     /// use scroll::Buffer;
     /// let buffer = Buffer::with(0x0u8, 10);
     pub fn with (seed: u8, size: usize) -> Self {
-        Buffer { inner: vec![seed; size] }
+        Buffer { inner: vec![seed; size], position: 0 }
     }
     /// Tries to suck the bytes out from `R` and create a new `Buffer` from it.
     /// # Example
@@ -49,10 +71,11 @@ impl Buffer {
     /// // this could be a `File` also
     /// let cursor = Cursor::new(bytes);
     /// let buffer = Buffer::try_from(cursor).unwrap();
+    #[cfg(feature = "std")]
     pub fn try_from<R: Read> (mut file: R) -> io::Result<Buffer> {
         let mut inner = Vec::new();
         file.read_to_end(&mut inner)?;
-        Ok(Buffer { inner: inner })
+        Ok(Buffer { inner, position: 0 })
     }
     pub fn as_slice (&self) -> &[u8] {
         self.inner.as_slice()
@@ -61,6 +84,14 @@ impl Buffer {
     pub fn into_inner(self) -> Vec<u8> {
         self.inner
     }
+    /// Returns the current position used by the `Read`, `Write` and `Seek` impls
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    /// Sets the current position used by the `Read`, `Write` and `Seek` impls; it may be past the end of the buffer
+    pub fn set_position(&mut self, position: usize) {
+        self.position = position;
+    }
 }
 
 // these gets us Pread, Pwrite, Gread, Gwrite, Greadable... abstraction ftw
@@ -98,19 +129,123 @@ impl DerefMut for Buffer {
     }
 }
 
-// this (will) gets us Lread
+// this gets us Lread
+#[cfg(feature = "std")]
 impl Read for Buffer {
     fn read (&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        Read::read(&mut self.inner.as_slice(), buf)
+        let start = ::core::cmp::min(self.position, self.inner.len());
+        let count = Read::read(&mut &self.inner[start..], buf)?;
+        self.position = start + count;
+        Ok(count)
     }
 }
 
 // this gets us Lwrite
+#[cfg(feature = "std")]
 impl Write for Buffer {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        Write::write(&mut self.inner.as_mut_slice(), buf)
+        let end = match self.position.checked_add(buf.len()) {
+            Some(end) => end,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "write past an overflowing position")),
+        };
+        if end > self.inner.len() {
+            self.inner.resize(end, 0);
+        }
+        self.inner[self.position..end].copy_from_slice(buf);
+        self.position = end;
+        Ok(buf.len())
     }
     fn flush(&mut self) -> io::Result<()> {
-        Write::flush(&mut self.inner.as_mut_slice())
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Seek for Buffer {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, delta) = match pos {
+            SeekFrom::Start(position) => (position, 0),
+            SeekFrom::End(delta) => (self.inner.len() as u64, delta),
+            SeekFrom::Current(delta) => (self.position as u64, delta),
+        };
+        let position = if delta >= 0 {
+            base.checked_add(delta as u64)
+        } else {
+            base.checked_sub(delta.wrapping_neg() as u64)
+        };
+        match position.and_then(|position| usize::try_from(position).ok().map(|index| (position, index))) {
+            Some((position, index)) => {
+                self.position = index;
+                Ok(position)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Write, Seek, SeekFrom};
+    use super::Buffer;
+    use super::super::{Lread, Lwrite, LE, BE};
+
+    #[test]
+    fn read_advances() {
+        let mut buffer = Buffer::new([1u8, 2, 3, 4, 5]);
+        let mut buf = [0u8; 2];
+        assert_eq!(buffer.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [1, 2]);
+        assert_eq!(buffer.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [3, 4]);
+        assert_eq!(buffer.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], 5);
+        assert_eq!(buffer.read(&mut buf).unwrap(), 0);
+        assert_eq!(buffer.position(), 5);
+    }
+
+    #[test]
+    fn write_advances_and_grows() {
+        let mut buffer = Buffer::with(0xff, 2);
+        buffer.write_all(&[1, 2, 3]).unwrap();
+        buffer.write_all(&[4]).unwrap();
+        assert_eq!(buffer.as_slice(), &[1, 2, 3, 4]);
+        buffer.set_position(6);
+        buffer.write_all(&[7]).unwrap();
+        assert_eq!(buffer.as_slice(), &[1, 2, 3, 4, 0, 0, 7]);
+        buffer.seek(SeekFrom::Start(1)).unwrap();
+        buffer.write_all(&[0xb]).unwrap();
+        assert_eq!(buffer.as_slice(), &[1, 0xb, 3, 4, 0, 0, 7]);
+    }
+
+    #[test]
+    fn seek() {
+        let mut buffer = Buffer::with(0, 8);
+        assert_eq!(buffer.seek(SeekFrom::End(-2)).unwrap(), 6);
+        assert_eq!(buffer.seek(SeekFrom::Current(-6)).unwrap(), 0);
+        assert!(buffer.seek(SeekFrom::Current(-1)).is_err());
+        assert_eq!(buffer.position(), 0);
+        assert_eq!(buffer.seek(SeekFrom::End(4)).unwrap(), 12);
+        let mut buf = [0u8; 1];
+        assert_eq!(buffer.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn lread_lwrite_like_cursor() {
+        let mut buffer = Buffer::default();
+        let mut cursor = Cursor::new(Vec::new());
+        buffer.lwrite_with(0xdeadbeefu32, LE).unwrap();
+        cursor.lwrite_with(0xdeadbeefu32, LE).unwrap();
+        buffer.lwrite_with(-2i16, BE).unwrap();
+        cursor.lwrite_with(-2i16, BE).unwrap();
+        buffer.lwrite(0.5f64).unwrap();
+        cursor.lwrite(0.5f64).unwrap();
+        assert_eq!(buffer.as_slice(), cursor.get_ref().as_slice());
+        buffer.seek(SeekFrom::Start(0)).unwrap();
+        cursor.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(buffer.lread_with::<u32>(LE).unwrap(), cursor.lread_with::<u32>(LE).unwrap());
+        assert_eq!(buffer.lread_with::<i16>(BE).unwrap(), cursor.lread_with::<i16>(BE).unwrap());
+        assert_eq!(buffer.lread::<f64>().unwrap(), cursor.lread::<f64>().unwrap());
+        assert!(buffer.lread::<u8>().is_err());
+        assert!(cursor.lread::<u8>().is_err());
     }
 }
@@ -64,9 +64,19 @@ use core::ptr::copy_nonoverlapping;
 use core::mem::transmute;
 use core::mem::size_of;
 use core::str;
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
 
 use error;
 use endian;
+use leb128::Uleb128;
+use pod::{self, Pod};
 
 /// The default parsing context; use this when the context isn't important for your datatype
 pub type DefaultCtx = endian::Endian;
@@ -74,22 +84,63 @@ pub type DefaultCtx = endian::Endian;
 /// Convenience constant for the default parsing context
 pub const CTX: DefaultCtx = endian::NATIVE;
 
+/// The width and encoding of a length prefix for a `StrCtx::Prefixed` string
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LengthPrefix {
+    /// A single byte length
+    U8,
+    /// A two byte length, in the endianness of the enclosing `StrCtx`
+    U16,
+    /// A four byte length, in the endianness of the enclosing `StrCtx`
+    U32,
+    /// A variable length unsigned leb128 length, e.g., protobuf or wasm strings
+    Uleb128,
+}
+
 /// The parsing context for converting a byte sequence to a `&str`
 ///
-/// `StrCtx` specifies what byte delimiter to use, and defaults to C-style null terminators. Be careful.
-#[derive(Debug, Copy, Clone)]
-pub struct StrCtx {
-    pub delimiter: u8
+/// `StrCtx` specifies how the extent of the string is determined, and defaults to C-style null terminators. Be careful.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx, Pread, Pwrite, BE};
+/// use scroll::ctx::{StrCtx, LengthPrefix};
+/// // a Mach-O style `segname[16]`, padded with nulls
+/// let segname: &[u8] = b"__TEXT\0\0\0\0\0\0\0\0\0\0";
+/// let name: &str = segname.pread_with(0, StrCtx::Padded(16, 0)).unwrap();
+/// assert_eq!(name, "__TEXT");
+/// // a big endian u16 length prefixed string
+/// let mut bytes = [0u8; 7];
+/// bytes.pwrite_with("hello", 0, StrCtx::Prefixed(LengthPrefix::U16, BE)).unwrap();
+/// assert_eq!(&bytes, b"\x00\x05hello");
+/// let hello: &str = bytes.pread_with(0, StrCtx::Prefixed(LengthPrefix::U16, BE)).unwrap();
+/// assert_eq!(hello, "hello");
+/// // the delimiter is written out too
+/// let mut bytes = [0xffu8; 3];
+/// bytes.pwrite_with("hi", 0, ctx::NULL).unwrap();
+/// assert_eq!(&bytes, b"hi\0");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StrCtx {
+    /// The string ends at the first occurrence of the delimiter byte, or the end of the input; the delimiter is written after the string
+    Delimiter(u8),
+    /// The string is exactly this many bytes long
+    Length(usize),
+    /// The string lives in a fixed width field of this many bytes, and ends at the first occurrence of the padding byte;
+    /// the remainder of the field is filled with the padding byte when written
+    Padded(usize, u8),
+    /// The string's byte length immediately precedes it, encoded as a `LengthPrefix` in the given byte order
+    Prefixed(LengthPrefix, endian::Endian),
 }
 
 /// A C-style, null terminator based delimiter for a `StrCtx`
-pub const NULL: StrCtx = StrCtx { delimiter: 0 };
+pub const NULL: StrCtx = StrCtx::Delimiter(0);
 /// A space-based delimiter for a `StrCtx`
-pub const SPACE: StrCtx = StrCtx { delimiter: 0x20 };
+pub const SPACE: StrCtx = StrCtx::Delimiter(0x20);
 /// A newline-based delimiter for a `StrCtx`
-pub const RET: StrCtx = StrCtx { delimiter: 0x0a };
+pub const RET: StrCtx = StrCtx::Delimiter(0x0a);
 /// A tab-based delimiter for a `StrCtx`
-pub const TAB: StrCtx = StrCtx { delimiter: 0x09 };
+pub const TAB: StrCtx = StrCtx::Delimiter(0x09);
 
 impl Default for StrCtx {
     #[inline]
@@ -100,7 +151,7 @@ impl Default for StrCtx {
 
 impl From<u8> for StrCtx {
     fn from(delimiter: u8) -> Self {
-        StrCtx { delimiter: delimiter }
+        StrCtx::Delimiter(delimiter)
     }
 }
 
@@ -117,6 +168,38 @@ pub trait TryFromCtx<'a, Ctx: Copy = (usize, DefaultCtx), This: ?Sized = [u8]> w
     fn try_from_ctx(from: &'a This, ctx: Ctx) -> Result<Self, Self::Error>;
 }
 
+/// Tries to read `Self` from `This` using the context `Ctx`, and returns it along with how many bytes of `This` it consumed
+///
+/// This is what allows variable length types, e.g., leb128s or delimited strings, to be read one after another, since their size is only known after they've been parsed.
+/// Any type implementing `TryFromCtx` and `SizeWith`, whose error converts from a scroll `Error`, gets this for free, consuming `size_with` bytes;
+/// those bytes are bounds checked as a whole before reading, so the error covers all of them, and not just the element that ran off the end.
+pub trait TryReadCtx<'a, Ctx: Copy = (usize, DefaultCtx), This: ?Sized = [u8]> where Self: 'a + Sized {
+    type Error;
+    fn try_read_ctx(from: &'a This, ctx: Ctx) -> Result<(Self, usize), Self::Error>;
+    /// The number of bytes every `Self` consumes with `ctx`, if it doesn't depend on the value; this lets readers check the bounds of many reads up front
+    #[inline]
+    fn fixed_size(_ctx: &Ctx) -> Option<usize> {
+        None
+    }
+}
+
+impl<'a, Ctx: Copy, N> TryReadCtx<'a, (usize, Ctx)> for N
+    where N: TryFromCtx<'a, (usize, Ctx)> + SizeWith<Ctx, Units = usize>,
+          <N as TryFromCtx<'a, (usize, Ctx)>>::Error: From<error::Error> {
+    type Error = <N as TryFromCtx<'a, (usize, Ctx)>>::Error;
+    #[inline]
+    fn try_read_ctx(src: &'a [u8], (offset, ctx): (usize, Ctx)) -> Result<(Self, usize), Self::Error> {
+        let size = N::size_with(&ctx);
+        error::check_range(offset, size, src.len())?;
+        let n = N::try_from_ctx(src, (offset, ctx))?;
+        Ok((n, size))
+    }
+    #[inline]
+    fn fixed_size((_, ctx): &(usize, Ctx)) -> Option<usize> {
+        Some(N::size_with(ctx))
+    }
+}
+
 /// Writes `Self` into `This` using the context `Ctx`
 pub trait IntoCtx<Ctx: Copy = DefaultCtx, This: ?Sized = [u8]>: Sized {
     fn into_ctx(self, &mut This, ctx: Ctx);
@@ -128,6 +211,39 @@ pub trait TryIntoCtx<Ctx: Copy = (usize, DefaultCtx), This: ?Sized = [u8]>: Size
     fn try_into_ctx(self, &mut This, ctx: Ctx) -> Result<(), Self::Error>;
 }
 
+/// Tries to write `Self` into `This` using the context `Ctx`, and returns how many bytes of `This` it wrote
+///
+/// This is the write side of `TryReadCtx`: it lets variable length types, e.g., leb128s, byte slices or delimited strings, be written one after another with `gwrite`,
+/// since their size is only known once they've been encoded.
+/// Any type implementing `TryIntoCtx` and `SizeWith`, whose error converts from a scroll `Error`, gets this for free, writing `size_with` bytes;
+/// those bytes are bounds checked as a whole first, so nothing is written unless all of them fit.
+pub trait TryWriteCtx<Ctx: Copy = (usize, DefaultCtx), This: ?Sized = [u8]>: Sized {
+    type Error;
+    fn try_write_ctx(self, dst: &mut This, ctx: Ctx) -> Result<usize, Self::Error>;
+    /// The number of bytes every `Self` writes with `ctx`, if it doesn't depend on the value; this lets writers check the bounds before writing anything
+    #[inline]
+    fn fixed_size(_ctx: &Ctx) -> Option<usize> {
+        None
+    }
+}
+
+impl<Ctx: Copy, N> TryWriteCtx<(usize, Ctx)> for N
+    where N: TryIntoCtx<(usize, Ctx)> + SizeWith<Ctx, Units = usize>,
+          <N as TryIntoCtx<(usize, Ctx)>>::Error: From<error::Error> {
+    type Error = <N as TryIntoCtx<(usize, Ctx)>>::Error;
+    #[inline]
+    fn try_write_ctx(self, dst: &mut [u8], (offset, ctx): (usize, Ctx)) -> Result<usize, Self::Error> {
+        let size = N::size_with(&ctx);
+        error::check_range(offset, size, dst.len())?;
+        self.try_into_ctx(dst, (offset, ctx))?;
+        Ok(size)
+    }
+    #[inline]
+    fn fixed_size((_, ctx): &(usize, Ctx)) -> Option<usize> {
+        Some(N::size_with(ctx))
+    }
+}
+
 pub trait RefFrom<This: ?Sized = [u8], I = usize> {
     type Error;
     #[inline]
@@ -147,6 +263,67 @@ pub trait TryRefIntoCtx<Ctx: Copy = (usize, usize, DefaultCtx), This: ?Sized = [
     fn try_ref_into_ctx(self, &mut This, ctx: Ctx) -> Result<(), Self::Error>;
 }
 
+/// Tries to read `Self` from a `std::io::Read` stream using the context `Ctx`, consuming exactly the bytes `Self` occupies
+///
+/// Unlike `TryFromCtx`, implementors don't need to know their size up front, so variable length types, e.g., leb128s or delimited strings,
+/// can be decoded incrementally; use [Lread::lread_try_with](../trait.Lread.html#method.lread_try_with) to call it.
+/// Any type implementing `TryFromCtx` for all lifetimes (i.e., it doesn't borrow from its input) and `SizeWith` gets this for free.
+/// Only present when `std` feature is used (the default).
+///
+/// **NB**: implementations may perform many small reads, so wrap unbuffered streams like a `File` in a `std::io::BufReader`
+#[cfg(feature = "std")]
+pub trait TryFromStream<Ctx: Copy = DefaultCtx>: Sized {
+    type Error;
+    fn try_from_stream<R: Read + ?Sized>(src: &mut R, ctx: Ctx) -> Result<Self, Self::Error>;
+}
+
+/// Tries to write `Self` into a `std::io::Write` stream using the context `Ctx`
+///
+/// Any type implementing `TryIntoCtx` and `SizeWith` gets this for free; use [Lwrite::lwrite_try_with](../trait.Lwrite.html#method.lwrite_try_with) to call it.
+/// Only present when `std` feature is used (the default).
+#[cfg(feature = "std")]
+pub trait TryIntoStream<Ctx: Copy = DefaultCtx>: Sized {
+    type Error;
+    fn try_into_stream<W: Write + ?Sized>(self, dst: &mut W, ctx: Ctx) -> Result<(), Self::Error>;
+}
+
+/// Calls `f` with a zeroed scratch buffer of `size` bytes, which only allocates if `size` is larger than 256
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn with_scratch<T, F: FnOnce(&mut [u8]) -> T>(size: usize, f: F) -> T {
+    let mut scratch = [0u8; 256];
+    if size <= scratch.len() {
+        f(&mut scratch[..size])
+    } else {
+        f(&mut vec![0u8; size])
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Ctx: Copy, N> TryFromStream<Ctx> for N where N: for<'a> TryFromCtx<'a, (usize, Ctx), Error = error::Error> + SizeWith<Ctx, Units = usize> {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_stream<R: Read + ?Sized>(src: &mut R, ctx: Ctx) -> error::Result<Self> {
+        with_scratch(N::size_with(&ctx), |buf| {
+            src.read_exact(buf)?;
+            N::try_from_ctx(buf, (0, ctx))
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Ctx: Copy, N> TryIntoStream<Ctx> for N where N: TryIntoCtx<(usize, Ctx), Error = error::Error> + SizeWith<Ctx, Units = usize> {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_stream<W: Write + ?Sized>(self, dst: &mut W, ctx: Ctx) -> error::Result<()> {
+        with_scratch(N::size_with(&ctx), |buf| {
+            self.try_into_ctx(buf, (0, ctx))?;
+            dst.write_all(buf)?;
+            Ok(())
+        })
+    }
+}
+
 /// Gets the size of `Self` with a `Ctx`, and in `Self::Units`. Implementors can then call `Gread` related functions
 ///
 /// The rationale behind this trait is to:
@@ -159,28 +336,12 @@ pub trait SizeWith<Ctx = DefaultCtx> {
     fn size_with(ctx: &Ctx) -> Self::Units;
 }
 
-impl<T> TryRefFromCtx<(usize, usize, super::Endian), T> for [u8] where T: AsRef<[u8]> {
+// `count` is in `T`s, so for `[u8]` it is the number of bytes; see `Pod` for what borrowing wider types checks
+impl<T, This: ?Sized> TryRefFromCtx<(usize, usize, super::Endian), This> for [T] where T: Pod, This: AsRef<[u8]> {
     type Error = error::Error;
     #[inline]
-    fn try_ref_from_ctx(b: &T, (offset, count, _): (usize, usize, super::Endian)) -> error::Result<&[u8]> {
-        let b = b.as_ref();
-        if offset + count > b.len () {
-            Err(error::Error::BadRange{range: (offset..offset+count), size: b.len()})
-        } else {
-            Ok(&b[offset..(offset+count)])
-        }
-    }
-}
-
-impl TryRefFromCtx for [u8] {
-    type Error = error::Error;
-    #[inline]
-    fn try_ref_from_ctx(b: &[u8], (offset, count, _): (usize, usize, super::Endian)) -> error::Result<&[u8]> {
-        if offset + count > b.len () {
-            Err(error::Error::BadRange{range: (offset..offset+count), size: b.len()})
-        } else {
-            Ok(&b[offset..(offset+count)])
-        }
+    fn try_ref_from_ctx(b: &This, (offset, count, le): (usize, usize, super::Endian)) -> error::Result<&[T]> {
+        pod::slice_from_bytes(b.as_ref(), offset, count, le)
     }
 }
 
@@ -188,14 +349,11 @@ impl TryRefFromCtx for str {
     type Error = error::Error;
     #[inline]
     fn try_ref_from_ctx(b: &[u8], (offset, count, _): (usize, usize, super::Endian)) -> error::Result<&str> {
-        if offset + count > b.len () {
-            Err(error::Error::BadRange{range: (offset..offset+count), size: b.len()})
-        } else {
-            let bytes = &b[offset..(offset+count)];
-            str::from_utf8(bytes).map_err(| _err | {
-                error::Error::BadInput{ range: offset..offset+count, size: bytes.len(), msg: "invalid utf8" }
-            })
-        }
+        let end = error::check_range(offset, count, b.len())?;
+        let bytes = &b[offset..end];
+        str::from_utf8(bytes).map_err(| _err | {
+            error::Error::BadInput{ range: offset..end, size: bytes.len(), msg: "invalid utf8" }
+        })
     }
 }
 
@@ -204,14 +362,11 @@ impl<T> TryRefFromCtx<(usize, usize, super::Endian), T> for str where T: AsRef<[
     #[inline]
     fn try_ref_from_ctx(b: &T, (offset, count, _): (usize, usize, super::Endian)) -> error::Result<&str> {
         let b = b.as_ref();
-        if offset + count > b.len () {
-            Err(error::Error::BadRange{range: (offset..offset+count), size: b.len()})
-        } else {
-            let bytes = &b[offset..(offset+count)];
-            str::from_utf8(bytes).map_err(| _err | {
-                error::Error::BadInput{ range: offset..offset+count, size: bytes.len(), msg: "invalid utf8" }
-            })
-        }
+        let end = error::check_range(offset, count, b.len())?;
+        let bytes = &b[offset..end];
+        str::from_utf8(bytes).map_err(| _err | {
+            error::Error::BadInput{ range: offset..end, size: bytes.len(), msg: "invalid utf8" }
+        })
     }
 }
 
@@ -224,6 +379,8 @@ macro_rules! signed_to_unsigned {
     (u32) => {u32};
     (i64) => {u64};
     (u64) => {u64};
+    (i128) => {u128};
+    (u128) => {u128};
     (f32) => {u32};
     (f64) => {u64};
 }
@@ -251,12 +408,9 @@ macro_rules! into_ctx_impl {
             type Error = error::Error;
             #[inline]
             fn try_into_ctx(self, dst: &mut [u8], (offset, le): (usize, super::Endian)) -> error::Result<()> {
-                if offset + $size > dst.len () {
-                    Err(error::Error::BadRange{range: offset..offset+$size, size: dst.len()})
-                } else {
-                    <$typ as IntoCtx<$ctx>>::into_ctx(self, &mut dst[offset..(offset+$size)], le);
-                    Ok(())
-                }
+                let end = error::check_range(offset, $size, dst.len())?;
+                <$typ as IntoCtx<$ctx>>::into_ctx(self, &mut dst[offset..end], le);
+                Ok(())
             }
         }
     }
@@ -283,11 +437,8 @@ macro_rules! from_ctx_impl {
             type Error = error::Error;
             #[inline]
             fn try_from_ctx(src: &'a [u8], (offset, le): (usize, $ctx)) -> error::Result<Self> {
-                if offset + $size > src.len () {
-                    Err(error::Error::BadRange{range: (offset..offset+$size), size: src.len()})
-                } else {
-                    Ok(FromCtx::from_ctx(&src[offset..(offset + $size)], le))
-                }
+                let end = error::check_range(offset, $size, src.len())?;
+                Ok(FromCtx::from_ctx(&src[offset..end], le))
             }
         }
         // as ref
@@ -312,11 +463,8 @@ macro_rules! from_ctx_impl {
             #[inline]
             fn try_from_ctx(src: &'a T, (offset, le): (usize, $ctx)) -> error::Result<Self> {
                 let src = src.as_ref();
-                if offset + $size > src.len () {
-                    Err(error::Error::BadRange{range: (offset..offset+$size), size: src.len()})
-                } else {
-                    Ok(FromCtx::from_ctx(&src[offset..(offset + $size)], le))
-                }
+                let end = error::check_range(offset, $size, src.len())?;
+                Ok(FromCtx::from_ctx(&src[offset..end], le))
             }
         }
 
@@ -337,6 +485,8 @@ ctx_impl!(u32, 4);
 ctx_impl!(i32, 4);
 ctx_impl!(u64, 8);
 ctx_impl!(i64, 8);
+ctx_impl!(u128, 16);
+ctx_impl!(i128, 16);
 
 macro_rules! from_ctx_float_impl {
     ($typ:tt, $size:expr, $ctx:ty) => {
@@ -359,11 +509,8 @@ macro_rules! from_ctx_float_impl {
             type Error = error::Error;
             #[inline]
             fn try_from_ctx(src: &'a [u8], (offset, le): (usize, $ctx)) -> error::Result<Self> {
-                if offset + $size > src.len () {
-                    Err(error::Error::BadRange{range: (offset..offset+$size), size: src.len()})
-                } else {
-                    Ok(FromCtx::from_ctx(&src[offset..(offset + $size)], le))
-                }
+                let end = error::check_range(offset, $size, src.len())?;
+                Ok(FromCtx::from_ctx(&src[offset..end], le))
             }
         }
     }
@@ -380,6 +527,8 @@ into_ctx_impl!(u32, 4, super::Endian);
 into_ctx_impl!(i32, 4, super::Endian);
 into_ctx_impl!(u64, 8, super::Endian);
 into_ctx_impl!(i64, 8, super::Endian);
+into_ctx_impl!(u128, 16, super::Endian);
+into_ctx_impl!(i128, 16, super::Endian);
 
 macro_rules! into_ctx_float_impl {
     ($typ:tt, $size:expr, $ctx:ty) => {
@@ -394,12 +543,9 @@ macro_rules! into_ctx_float_impl {
             type Error = error::Error;
             #[inline]
             fn try_into_ctx(self, dst: &mut [u8], (offset, le): (usize, super::Endian)) -> error::Result<()> {
-                if offset + $size > dst.len () {
-                    Err(error::Error::BadRange{range: offset..offset+$size, size: dst.len()})
-                } else {
-                    <$typ as IntoCtx<$ctx>>::into_ctx(self, &mut dst[offset..(offset+$size)], le);
-                    Ok(())
-                }
+                let end = error::check_range(offset, $size, dst.len())?;
+                <$typ as IntoCtx<$ctx>>::into_ctx(self, &mut dst[offset..end], le);
+                Ok(())
             }
         }
     }
@@ -428,22 +574,98 @@ fn get_str_delimiter_offset(bytes: &[u8], idx: usize, delimiter: u8) -> usize {
     i
 }
 
+#[inline]
+fn str_from_utf8(src: &[u8], offset: usize, count: usize) -> error::Result<&str> {
+    let end = error::check_range(offset, count, src.len())?;
+    let bytes = &src[offset..end];
+    str::from_utf8(bytes).map_err(| _err | {
+        error::Error::BadInput{ range: offset..end, size: bytes.len(), msg: "invalid utf8" }
+    })
+}
+
+/// Reads the length prefix at `offset`, returning the string's byte length and the size of the prefix
+#[inline]
+pub(crate) fn read_length_prefix(src: &[u8], offset: usize, prefix: LengthPrefix, le: endian::Endian) -> error::Result<(usize, usize)> {
+    use pread::Pread;
+    match prefix {
+        LengthPrefix::U8 => Ok((src.pread_with::<u8>(offset, le)? as usize, 1)),
+        LengthPrefix::U16 => Ok((src.pread_with::<u16>(offset, le)? as usize, 2)),
+        LengthPrefix::U32 => Ok((src.pread_with::<u32>(offset, le)? as usize, 4)),
+        LengthPrefix::Uleb128 => {
+            let uleb = src.pread::<Uleb128>(offset)?;
+            Ok((u64::from(uleb) as usize, uleb.size()))
+        },
+    }
+}
+
+/// Finds the string data at `offset` using `ctx`, and returns its range in `src` along with the number of bytes consumed, including any delimiter, padding or length prefix
+fn str_extent(src: &[u8], offset: usize, ctx: StrCtx) -> error::Result<(Range<usize>, usize)> {
+    let len = src.len();
+    let (start, count, consumed) = match ctx {
+        StrCtx::Delimiter(delimiter) => {
+            if offset >= len {
+                return Err(error::Error::BadOffset(offset))
+            }
+            let delimiter_offset = get_str_delimiter_offset(src, offset, delimiter);
+            let count = delimiter_offset - offset;
+            // the delimiter is consumed too, unless the string ran to the end of `src` without one
+            (offset, count, if delimiter_offset < len { count + 1 } else { count })
+        },
+        StrCtx::Length(count) => (offset, count, count),
+        StrCtx::Padded(width, pad) => {
+            let end = error::check_range(offset, width, len)?;
+            let field = &src[offset..end];
+            (offset, field.iter().position(|&byte| byte == pad).unwrap_or(width), width)
+        },
+        StrCtx::Prefixed(prefix, le) => {
+            let (count, size) = read_length_prefix(src, offset, prefix, le)?;
+            // the prefix was read, so `offset + size` is in bounds; it's the untrusted count that may overflow
+            (offset + size, count, size.saturating_add(count))
+        },
+    };
+    let end = error::check_range(start, count, len)?;
+    Ok((start..end, consumed))
+}
+
+/// Reads a `&str` from `src` using the extent specified by the `StrCtx`, and returns it with the number of bytes consumed
+fn read_str(src: &[u8], offset: usize, ctx: StrCtx) -> error::Result<(&str, usize)> {
+    let (range, consumed) = str_extent(src, offset, ctx)?;
+    Ok((str_from_utf8(src, range.start, range.len())?, consumed))
+}
+
 impl<'a> TryFromCtx<'a, (usize, StrCtx)> for &'a str {
     type Error = error::Error;
     #[inline]
-    /// Read a `&str` from `src` using `delimiter`
-    fn try_from_ctx(src: &'a [u8], (offset, StrCtx {delimiter}): (usize, StrCtx)) -> error::Result<Self> {
-        let len = src.len();
-        if offset >= len {
-            return Err(error::Error::BadOffset(offset))
-        }
-        let delimiter_offset = get_str_delimiter_offset(src, offset, delimiter);
-        let count = delimiter_offset - offset;
-        if count == 0 { return Ok("") }
-        let bytes = &src[offset..(offset+count)];
-        str::from_utf8(bytes).map_err(| _err | {
-            error::Error::BadInput{ range: offset..offset+count, size: bytes.len(), msg: "invalid utf8" }
-        })
+    /// Read a `&str` from `src` using the extent specified by the `StrCtx`
+    fn try_from_ctx(src: &'a [u8], (offset, ctx): (usize, StrCtx)) -> error::Result<Self> {
+        read_str(src, offset, ctx).map(|(s, _)| s)
+    }
+}
+
+impl<'a> TryReadCtx<'a, (usize, StrCtx)> for &'a str {
+    type Error = error::Error;
+    #[inline]
+    fn try_read_ctx(src: &'a [u8], (offset, ctx): (usize, StrCtx)) -> error::Result<(Self, usize)> {
+        read_str(src, offset, ctx)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TryFromCtx<'a, (usize, StrCtx)> for String {
+    type Error = error::Error;
+    #[inline]
+    /// Read an owned `String` from `src` using the extent specified by the `StrCtx`
+    fn try_from_ctx(src: &'a [u8], (offset, ctx): (usize, StrCtx)) -> error::Result<Self> {
+        read_str(src, offset, ctx).map(|(s, _)| String::from(s))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TryReadCtx<'a, (usize, StrCtx)> for String {
+    type Error = error::Error;
+    #[inline]
+    fn try_read_ctx(src: &'a [u8], (offset, ctx): (usize, StrCtx)) -> error::Result<(Self, usize)> {
+        read_str(src, offset, ctx).map(|(s, consumed)| (String::from(s), consumed))
     }
 }
 
@@ -460,27 +682,213 @@ impl<'a> TryIntoCtx<(usize, DefaultCtx)> for &'a [u8] {
     type Error = error::Error;
     #[inline]
     fn try_into_ctx(self, dst: &mut [u8], (uoffset, _): (usize, DefaultCtx)) -> error::Result<()> {
-        let src_len = self.len() as isize;
-        let dst_len = dst.len() as isize;
-        let offset = uoffset as isize;
-        // if src_len < 0 || dst_len < 0 || offset < 0 {
-        //     return Err(error::Error::BadOffset(format!("requested operation has negative casts: src len: {} dst len: {} offset: {}", src_len, dst_len, offset)).into())
-        // }
-        if offset + src_len > dst_len {
-            Err(error::Error::BadRange{ range: uoffset..uoffset+self.len(), size: dst.len()})
-        } else {
-            unsafe { copy_nonoverlapping(self.as_ptr(), dst.as_mut_ptr().offset(offset as isize), src_len as usize) };
+        let end = error::check_range(uoffset, self.len(), dst.len())?;
+        dst[uoffset..end].copy_from_slice(self);
+        Ok(())
+    }
+}
+
+impl TryWriteCtx<(usize, DefaultCtx)> for &[u8] {
+    type Error = error::Error;
+    #[inline]
+    fn try_write_ctx(self, dst: &mut [u8], ctx: (usize, DefaultCtx)) -> error::Result<usize> {
+        self.try_into_ctx(dst, ctx)?;
+        Ok(self.len())
+    }
+}
+
+/// The size of the length prefix for a string of `len` bytes
+#[inline]
+pub(crate) fn length_prefix_size(prefix: LengthPrefix, len: usize) -> usize {
+    match prefix {
+        LengthPrefix::U8 => 1,
+        LengthPrefix::U16 => 2,
+        LengthPrefix::U32 => 4,
+        LengthPrefix::Uleb128 => Uleb128::new(len as u64).size(),
+    }
+}
+
+/// The number of bytes `len` bytes of string data occupy when written with `ctx`
+#[inline]
+fn str_ctx_size(len: usize, ctx: StrCtx) -> usize {
+    match ctx {
+        StrCtx::Delimiter(_) => len + 1,
+        StrCtx::Length(_) => len,
+        StrCtx::Padded(width, _) => width,
+        StrCtx::Prefixed(prefix, _) => length_prefix_size(prefix, len) + len,
+    }
+}
+
+/// Writes the string data `bytes` into `dst` at `offset` using `ctx`, i.e., including any delimiter, padding or length prefix
+fn write_str_bytes(bytes: &[u8], dst: &mut [u8], offset: usize, ctx: StrCtx) -> error::Result<()> {
+    use pwrite::Pwrite;
+    let len = bytes.len();
+    match ctx {
+        StrCtx::Delimiter(delimiter) => {
+            error::check_range(offset, len.saturating_add(1), dst.len())?;
+            dst.pwrite(bytes, offset)?;
+            dst[offset+len] = delimiter;
             Ok(())
-        }
+        },
+        StrCtx::Length(count) => {
+            if len != count {
+                return Err(error::Error::BadInput{range: offset..offset.saturating_add(len), size: count, msg: "string length does not match context length"})
+            }
+            dst.pwrite(bytes, offset)
+        },
+        StrCtx::Padded(width, pad) => {
+            if len > width {
+                return Err(error::Error::BadInput{range: offset..offset.saturating_add(len), size: width, msg: "string is wider than its padded field"})
+            }
+            let end = error::check_range(offset, width, dst.len())?;
+            dst.pwrite(bytes, offset)?;
+            for byte in &mut dst[(offset+len)..end] {
+                *byte = pad;
+            }
+            Ok(())
+        },
+        StrCtx::Prefixed(prefix, le) => {
+            let max = match prefix {
+                LengthPrefix::U8 => u8::MAX as u64,
+                LengthPrefix::U16 => u16::MAX as u64,
+                LengthPrefix::U32 => u32::MAX as u64,
+                LengthPrefix::Uleb128 => u64::MAX,
+            };
+            if len as u64 > max {
+                return Err(error::Error::BadInput{range: offset..offset.saturating_add(len), size: len, msg: "string is too long for its length prefix"})
+            }
+            let size = length_prefix_size(prefix, len);
+            error::check_range(offset, size.saturating_add(len), dst.len())?;
+            match prefix {
+                LengthPrefix::U8 => dst.pwrite_with(len as u8, offset, le)?,
+                LengthPrefix::U16 => dst.pwrite_with(len as u16, offset, le)?,
+                LengthPrefix::U32 => dst.pwrite_with(len as u32, offset, le)?,
+                LengthPrefix::Uleb128 => dst.pwrite(Uleb128::new(len as u64), offset)?,
+            }
+            dst.pwrite(bytes, offset + size)
+        },
     }
 }
 
 impl<'a> TryIntoCtx<(usize, StrCtx)> for &'a str {
     type Error = error::Error;
     #[inline]
-    fn try_into_ctx(self, dst: &mut [u8], (offset, _): (usize, StrCtx)) -> error::Result<()> {
-        let bytes = self.as_bytes();
-        TryIntoCtx::try_into_ctx(bytes, dst, (offset, CTX))
+    /// Write `self` into `dst` at `offset` using the `StrCtx`, i.e., including any delimiter, padding or length prefix
+    fn try_into_ctx(self, dst: &mut [u8], (offset, ctx): (usize, StrCtx)) -> error::Result<()> {
+        write_str_bytes(self.as_bytes(), dst, offset, ctx)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryIntoCtx<(usize, StrCtx)> for &String {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: (usize, StrCtx)) -> error::Result<()> {
+        self.as_str().try_into_ctx(dst, ctx)
+    }
+}
+
+impl TryWriteCtx<(usize, StrCtx)> for &str {
+    type Error = error::Error;
+    #[inline]
+    fn try_write_ctx(self, dst: &mut [u8], (offset, ctx): (usize, StrCtx)) -> error::Result<usize> {
+        write_str_bytes(self.as_bytes(), dst, offset, ctx)?;
+        Ok(str_ctx_size(self.len(), ctx))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryWriteCtx<(usize, StrCtx)> for &String {
+    type Error = error::Error;
+    #[inline]
+    fn try_write_ctx(self, dst: &mut [u8], ctx: (usize, StrCtx)) -> error::Result<usize> {
+        self.as_str().try_write_ctx(dst, ctx)
+    }
+}
+
+#[cfg(feature = "std")]
+/// Reads string data from `src` using `ctx`, consuming any delimiter, padding or length prefix
+fn read_str_bytes<R: Read + ?Sized>(src: &mut R, ctx: StrCtx) -> error::Result<Vec<u8>> {
+    fn read_exactly<R: Read + ?Sized>(src: &mut R, count: usize) -> error::Result<Vec<u8>> {
+        // don't trust `count` enough to preallocate it
+        let mut bytes = Vec::new();
+        src.take(count as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != count {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer").into())
+        }
+        Ok(bytes)
+    }
+    match ctx {
+        StrCtx::Delimiter(delimiter) => {
+            let mut bytes = Vec::new();
+            let mut byte = [0u8; 1];
+            loop {
+                src.read_exact(&mut byte)?;
+                if byte[0] == delimiter {
+                    return Ok(bytes)
+                }
+                bytes.push(byte[0]);
+            }
+        },
+        StrCtx::Length(count) => read_exactly(src, count),
+        StrCtx::Padded(width, pad) => {
+            let mut bytes = read_exactly(src, width)?;
+            let count = bytes.iter().position(|&byte| byte == pad).unwrap_or(width);
+            bytes.truncate(count);
+            Ok(bytes)
+        },
+        StrCtx::Prefixed(prefix, le) => {
+            let count = match prefix {
+                LengthPrefix::U8 => u8::try_from_stream(src, le)? as usize,
+                LengthPrefix::U16 => u16::try_from_stream(src, le)? as usize,
+                LengthPrefix::U32 => u32::try_from_stream(src, le)? as usize,
+                LengthPrefix::Uleb128 => u64::from(Uleb128::try_from_stream(src, le)?) as usize,
+            };
+            read_exactly(src, count)
+        },
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFromStream<StrCtx> for Vec<u8> {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_stream<R: Read + ?Sized>(src: &mut R, ctx: StrCtx) -> error::Result<Self> {
+        read_str_bytes(src, ctx)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFromStream<StrCtx> for String {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_stream<R: Read + ?Sized>(src: &mut R, ctx: StrCtx) -> error::Result<Self> {
+        let bytes = read_str_bytes(src, ctx)?;
+        let len = bytes.len();
+        String::from_utf8(bytes).map_err(| _err | {
+            error::Error::BadInput{ range: 0..len, size: len, msg: "invalid utf8" }
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryIntoStream<StrCtx> for &[u8] {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_stream<W: Write + ?Sized>(self, dst: &mut W, ctx: StrCtx) -> error::Result<()> {
+        let mut bytes = vec![0u8; str_ctx_size(self.len(), ctx)];
+        write_str_bytes(self, &mut bytes, 0, ctx)?;
+        dst.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryIntoStream<StrCtx> for &str {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_stream<W: Write + ?Sized>(self, dst: &mut W, ctx: StrCtx) -> error::Result<()> {
+        self.as_bytes().try_into_stream(dst, ctx)
     }
 }
 
@@ -505,6 +913,8 @@ sizeof_impl!(u32);
 sizeof_impl!(i32);
 sizeof_impl!(u64);
 sizeof_impl!(i64);
+sizeof_impl!(u128);
+sizeof_impl!(i128);
 sizeof_impl!(f32);
 sizeof_impl!(f64);
 sizeof_impl!(usize);
@@ -531,11 +941,8 @@ impl<'a> TryFromCtx<'a, (usize, super::Endian)> for usize where usize: FromCtx<s
     #[inline]
     fn try_from_ctx(src: &'a [u8], (offset, le): (usize, super::Endian)) -> error::Result<Self> {
         let size = ::core::mem::size_of::<usize>();
-        if offset + size > src.len () {
-            Err(error::Error::BadRange{range: offset..offset+size, size: src.len()})
-        } else {
-            Ok(FromCtx::from_ctx(&src[offset..(offset + size)], le))
-        }
+        let end = error::check_range(offset, size, src.len())?;
+        Ok(FromCtx::from_ctx(&src[offset..end], le))
     }
 }
 
@@ -557,23 +964,159 @@ impl TryIntoCtx<(usize, super::Endian)> for usize where usize: IntoCtx<super::En
     #[inline]
     fn try_into_ctx(self, dst: &mut [u8], (offset, le): (usize, super::Endian)) -> error::Result<()> {
         let size = ::core::mem::size_of::<usize>();
-        if offset + size > dst.len() {
-            Err(error::Error::BadRange{range: offset..offset+size, size: dst.len()})
-        } else {
-            <usize as IntoCtx<super::Endian>>::into_ctx(self, &mut dst[offset..(offset+size)], le);
-            Ok(())
+        let end = error::check_range(offset, size, dst.len())?;
+        <usize as IntoCtx<super::Endian>>::into_ctx(self, &mut dst[offset..end], le);
+        Ok(())
+    }
+}
+
+// Fixed size arrays are read and written element by element, threading the context through each, so e.g. a `[u32; 4]` respects the endianness.
+// Element offsets saturate rather than wrap, so an element which would start past `usize::MAX` fails its own bounds check
+
+impl<Ctx: Copy, T, const N: usize> SizeWith<Ctx> for [T; N] where T: SizeWith<Ctx, Units = usize> {
+    type Units = usize;
+    #[inline]
+    fn size_with(ctx: &Ctx) -> usize {
+        T::size_with(ctx) * N
+    }
+}
+
+impl<Ctx: Copy, T, const N: usize> FromCtx<Ctx> for [T; N] where T: FromCtx<Ctx> + SizeWith<Ctx, Units = usize> {
+    #[inline]
+    fn from_ctx(src: &[u8], ctx: Ctx) -> Self {
+        let size = T::size_with(&ctx);
+        ::core::array::from_fn(|i| T::from_ctx(&src[i * size..], ctx))
+    }
+}
+
+impl<'a, Ctx: Copy, T, const N: usize> TryFromCtx<'a, (usize, Ctx)> for [T; N] where T: TryFromCtx<'a, (usize, Ctx)> + SizeWith<Ctx, Units = usize> {
+    type Error = T::Error;
+    #[inline]
+    fn try_from_ctx(src: &'a [u8], (offset, ctx): (usize, Ctx)) -> Result<Self, Self::Error> {
+        let size = T::size_with(&ctx);
+        let mut error = None;
+        let items: [Option<T>; N] = ::core::array::from_fn(|i| {
+            if error.is_some() {
+                return None
+            }
+            match T::try_from_ctx(src, (offset.saturating_add(i.saturating_mul(size)), ctx)) {
+                Ok(item) => Some(item),
+                Err(err) => {
+                    error = Some(err);
+                    None
+                }
+            }
+        });
+        match error {
+            Some(err) => Err(err),
+            None => Ok(items.map(|item| item.unwrap())),
+        }
+    }
+}
+
+impl<Ctx: Copy, T, const N: usize> IntoCtx<Ctx> for [T; N] where T: IntoCtx<Ctx> + SizeWith<Ctx, Units = usize> {
+    #[inline]
+    fn into_ctx(self, dst: &mut [u8], ctx: Ctx) {
+        let size = T::size_with(&ctx);
+        for (i, item) in IntoIterator::into_iter(self).enumerate() {
+            item.into_ctx(&mut dst[i * size..], ctx);
+        }
+    }
+}
+
+impl<Ctx: Copy, T, const N: usize> TryIntoCtx<(usize, Ctx)> for [T; N] where T: TryIntoCtx<(usize, Ctx)> + SizeWith<Ctx, Units = usize> {
+    type Error = T::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], (offset, ctx): (usize, Ctx)) -> Result<(), Self::Error> {
+        let size = T::size_with(&ctx);
+        for (i, item) in IntoIterator::into_iter(self).enumerate() {
+            item.try_into_ctx(dst, (offset.saturating_add(i.saturating_mul(size)), ctx))?;
+        }
+        Ok(())
+    }
+}
+
+// Tuples are read and written field by field, back to back, threading the context through each;
+// every field must have the same error type
+
+macro_rules! tuple_ctx_impl {
+    ($($name:ident $var:ident),+) => {
+        impl<Ctx: Copy, $($name),+> SizeWith<Ctx> for ($($name,)+) where $($name: SizeWith<Ctx, Units = usize>),+ {
+            type Units = usize;
+            #[inline]
+            fn size_with(ctx: &Ctx) -> usize {
+                0 $(+ $name::size_with(ctx))+
+            }
+        }
+
+        impl<Ctx: Copy, $($name),+> FromCtx<Ctx> for ($($name,)+) where $($name: FromCtx<Ctx> + SizeWith<Ctx, Units = usize>),+ {
+            #[inline]
+            #[allow(unused_assignments)]
+            fn from_ctx(src: &[u8], ctx: Ctx) -> Self {
+                let mut offset = 0;
+                ($({
+                    let $var = $name::from_ctx(&src[offset..], ctx);
+                    offset += $name::size_with(&ctx);
+                    $var
+                },)+)
+            }
+        }
+
+        impl<'a, Ctx: Copy, E, $($name),+> TryFromCtx<'a, (usize, Ctx)> for ($($name,)+)
+            where $($name: TryFromCtx<'a, (usize, Ctx), Error = E> + SizeWith<Ctx, Units = usize>),+ {
+            type Error = E;
+            #[inline]
+            #[allow(unused_assignments)]
+            fn try_from_ctx(src: &'a [u8], (offset, ctx): (usize, Ctx)) -> Result<Self, E> {
+                let mut offset = offset;
+                Ok(($({
+                    let $var = $name::try_from_ctx(src, (offset, ctx))?;
+                    offset = offset.saturating_add($name::size_with(&ctx));
+                    $var
+                },)+))
+            }
+        }
+
+        impl<Ctx: Copy, $($name),+> IntoCtx<Ctx> for ($($name,)+) where $($name: IntoCtx<Ctx> + SizeWith<Ctx, Units = usize>),+ {
+            #[inline]
+            #[allow(unused_assignments)]
+            fn into_ctx(self, dst: &mut [u8], ctx: Ctx) {
+                let ($($var,)+) = self;
+                let mut offset = 0;
+                $(
+                    $var.into_ctx(&mut dst[offset..], ctx);
+                    offset += $name::size_with(&ctx);
+                )+
+            }
+        }
+
+        impl<Ctx: Copy, E, $($name),+> TryIntoCtx<(usize, Ctx)> for ($($name,)+)
+            where $($name: TryIntoCtx<(usize, Ctx), Error = E> + SizeWith<Ctx, Units = usize>),+ {
+            type Error = E;
+            #[inline]
+            #[allow(unused_assignments)]
+            fn try_into_ctx(self, dst: &mut [u8], (offset, ctx): (usize, Ctx)) -> Result<(), E> {
+                let ($($var,)+) = self;
+                let mut offset = offset;
+                $(
+                    $var.try_into_ctx(dst, (offset, ctx))?;
+                    offset = offset.saturating_add($name::size_with(&ctx));
+                )+
+                Ok(())
+            }
         }
     }
 }
 
-// example of marshalling to bytes, let's wait until const is an option
-// impl FromCtx for [u8; 10] {
-//     fn from_ctx(bytes: &[u8], _ctx: super::Endian) -> Self {
-//         let mut dst: Self = [0; 10];
-//         assert!(bytes.len() >= dst.len());
-//         unsafe {
-//             copy_nonoverlapping(bytes.as_ptr(), dst.as_mut_ptr(), dst.len());
-//         }
-//         dst
-//     }
-// }
+tuple_ctx_impl!(A a);
+tuple_ctx_impl!(A a, B b);
+tuple_ctx_impl!(A a, B b, C c);
+tuple_ctx_impl!(A a, B b, C c, D d);
+tuple_ctx_impl!(A a, B b, C c, D d, F f);
+tuple_ctx_impl!(A a, B b, C c, D d, F f, G g);
+tuple_ctx_impl!(A a, B b, C c, D d, F f, G g, H h);
+tuple_ctx_impl!(A a, B b, C c, D d, F f, G g, H h, J j);
+tuple_ctx_impl!(A a, B b, C c, D d, F f, G g, H h, J j, K k);
+tuple_ctx_impl!(A a, B b, C c, D d, F f, G g, H h, J j, K k, L l);
+tuple_ctx_impl!(A a, B b, C c, D d, F f, G g, H h, J j, K k, L l, M m);
+tuple_ctx_impl!(A a, B b, C c, D d, F f, G g, H h, J j, K k, L l, M m, N n);
@@ -1,3 +1,10 @@
+use core::fmt::{self, Display};
+use core::ops::Not;
+use core::str::FromStr;
+
+use ctx::{FromCtx, SizeWith};
+use error;
+
 #[derive(PartialEq, Eq, Copy, Debug, Clone)]
 /// The endianness (byte order) of a stream of bytes
 pub enum Endian {
@@ -10,7 +17,7 @@ pub const LE: Endian = Endian::Little;
 /// Big Endian byte order context
 pub const BE: Endian = Endian::Big;
 /// Network byte order context
-pub const NETWORK: Endian = Endian::Little;
+pub const NETWORK: Endian = Endian::Big;
 #[cfg(target_endian = "little")]
 /// The machine's native byte order
 pub const NATIVE: Endian = LE;
@@ -32,16 +39,136 @@ impl From<bool> for Endian {
     }
 }
 
+impl Not for Endian {
+    type Output = Endian;
+    #[inline]
+    fn not(self) -> Endian {
+        self.swap()
+    }
+}
+
+impl Display for Endian {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LE => write!(fmt, "little"),
+            BE => write!(fmt, "big"),
+        }
+    }
+}
+
+impl FromStr for Endian {
+    type Err = error::Error;
+    /// Parses `"little"`/`"le"`, `"big"`/`"be"`, `"network"` or `"native"`, ignoring case
+    fn from_str(s: &str) -> error::Result<Self> {
+        let eq = |name: &str| s.eq_ignore_ascii_case(name);
+        if eq("little") || eq("le") {
+            Ok(LE)
+        } else if eq("big") || eq("be") {
+            Ok(BE)
+        } else if eq("network") {
+            Ok(NETWORK)
+        } else if eq("native") {
+            Ok(NATIVE)
+        } else {
+            Err(error::Error::BadInput { range: 0..s.len(), size: s.len(), msg: "unknown endianness" })
+        }
+    }
+}
+
 impl Endian {
     #[inline]
     pub fn network() -> Endian {
-        Endian::Little
+        NETWORK
     }
     #[inline]
     pub fn is_little (&self) -> bool {
-        match *self {
-            LE => true,
-            _ => false,
+        matches!(*self, LE)
+    }
+    #[inline]
+    pub fn is_big (&self) -> bool {
+        matches!(*self, BE)
+    }
+    /// Returns the opposite byte order; also available as `!endian`
+    #[inline]
+    pub fn swap(self) -> Endian {
+        match self {
+            LE => BE,
+            BE => LE,
+        }
+    }
+    /// Detects the byte order of `bytes` by checking whether they begin with `magic` encoded in little or big endian byte order, if at all.
+    ///
+    /// Byte order marks are just a magic value which is written with the stream's endianness, e.g., `0xfeffu16` for UTF-16.
+    /// If `magic` reads the same in both byte orders, the native byte order is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::{Endian, LE, BE};
+    /// const MH_MAGIC: u32 = 0xfeedface;
+    /// // MH_CIGAM, i.e., a big endian Mach-O
+    /// let header = [0xfe, 0xed, 0xfa, 0xce];
+    /// assert_eq!(Endian::from_magic(&header, MH_MAGIC), Some(BE));
+    /// assert_eq!(Endian::from_magic(&[0xff, 0xfe, b'h', 0], 0xfeffu16), Some(LE));
+    /// assert_eq!(Endian::from_magic(&[0x7f, b'E'], 0xfeffu16), None);
+    /// // ELF's `e_ident[EI_DATA]` is 1 for little endian and 2 for big endian
+    /// let ei_data = 2u8;
+    /// assert_eq!(Endian::from(ei_data == 1), BE);
+    /// ```
+    pub fn from_magic<N>(bytes: &[u8], magic: N) -> Option<Endian>
+        where N: FromCtx<Endian> + SizeWith<Endian, Units = usize> + PartialEq {
+        if bytes.len() < N::size_with(&NATIVE) {
+            return None
         }
+        if N::from_ctx(bytes, NATIVE) == magic {
+            Some(NATIVE)
+        } else if N::from_ctx(bytes, !NATIVE) == magic {
+            Some(!NATIVE)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Endian, LE, BE, NETWORK, NATIVE};
+
+    #[test]
+    fn network_is_big() {
+        assert_eq!(NETWORK, BE);
+        assert_eq!(Endian::network(), BE);
+        assert!(NETWORK.is_big());
+        assert!(!NETWORK.is_little());
+    }
+
+    #[test]
+    fn swap() {
+        assert_eq!(LE.swap(), BE);
+        assert_eq!(!BE, LE);
+        assert_eq!(!!NATIVE, NATIVE);
+    }
+
+    #[test]
+    fn display_from_str() {
+        assert_eq!(format!("{}", LE), "little");
+        assert_eq!(format!("{}", BE), "big");
+        for endian in [LE, BE].iter() {
+            assert_eq!(endian.to_string().parse::<Endian>().unwrap(), *endian);
+        }
+        assert_eq!("LE".parse::<Endian>().unwrap(), LE);
+        assert_eq!("Be".parse::<Endian>().unwrap(), BE);
+        assert_eq!("network".parse::<Endian>().unwrap(), BE);
+        assert_eq!("native".parse::<Endian>().unwrap(), NATIVE);
+        assert!("middle".parse::<Endian>().is_err());
+    }
+
+    #[test]
+    fn from_magic() {
+        const MH_MAGIC_64: u32 = 0xfeedfacf;
+        assert_eq!(Endian::from_magic(&[0xcf, 0xfa, 0xed, 0xfe, 0], MH_MAGIC_64), Some(LE));
+        assert_eq!(Endian::from_magic(&[0xfe, 0xed, 0xfa, 0xcf], MH_MAGIC_64), Some(BE));
+        assert_eq!(Endian::from_magic(&[0xfe, 0xed, 0xfa], MH_MAGIC_64), None);
+        assert_eq!(Endian::from_magic(&[0, 0, 0xfe, 0xff], 0xfeffu32), Some(BE));
+        assert_eq!(Endian::from_magic(&[0x11, 0x11], 0x1111u16), Some(NATIVE));
     }
 }
@@ -2,6 +2,8 @@ use core::fmt::{self, Display};
 use core::result;
 use core::ops::Range;
 
+#[cfg(feature = "alloc")]
+use alloc::string::String;
 #[cfg(feature = "std")]
 use std::io;
 #[cfg(feature = "std")]
@@ -16,7 +18,15 @@ pub enum Error {
     BadRange { range: Range<usize>, size: usize },
     /// The data at the given range is invalid
     BadInput { range: Range<usize>, size: usize, msg: &'static str },
-    #[cfg(feature = "std")]
+    /// The requested offset plus the size of the read/write overflows a `usize`, e.g., a hostile offset from an untrusted header
+    Overflow { offset: usize, size: usize },
+    /// The offset can't be converted between the caller's offset type, e.g., a `u64` or `u32`, and a `usize` slice index; `ty` is the type it didn't fit in
+    BadOffsetType { offset: i128, ty: &'static str },
+    /// The data at `offset` isn't aligned in memory to the `align` bytes a borrowed reference to it needs
+    BadAlignment { offset: usize, align: usize },
+    /// `count` slots reserved for backpatching were never filled in; `offset` is where the first of them is
+    Unfilled { offset: u64, count: usize },
+    #[cfg(feature = "alloc")]
     /// A custom Scroll error for reporting messages to clients
     Custom(String),
     #[cfg(feature = "std")]
@@ -31,6 +41,10 @@ impl error::Error for Error {
             Error::BadOffset(_) => { "BadOffset" }
             Error::BadRange{ .. } => { "BadRange" }
             Error::BadInput{ .. } => { "BadInput" }
+            Error::Overflow{ .. } => { "Overflow" }
+            Error::BadOffsetType{ .. } => { "BadOffsetType" }
+            Error::BadAlignment{ .. } => { "BadAlignment" }
+            Error::Unfilled{ .. } => { "Unfilled" }
             Error::Custom(_) => { "Custom" }
             Error::IO(_) => { "IO" }
         }
@@ -40,6 +54,10 @@ impl error::Error for Error {
             Error::BadOffset(_) => { None }
             Error::BadRange{ .. } => { None }
             Error::BadInput{ .. }=> { None }
+            Error::Overflow{ .. } => { None }
+            Error::BadOffsetType{ .. } => { None }
+            Error::BadAlignment{ .. } => { None }
+            Error::Unfilled{ .. } => { None }
             Error::Custom(_) => { None }
             Error::IO(ref io) => { io.cause() }
         }
@@ -63,7 +81,19 @@ impl Display for Error {
             Error::BadInput{ref range, ref size, msg} => {
                 write!(fmt, "{} - range [{}..{}), len {}", msg, range.start, range.end, size)
             },
-            #[cfg(feature = "std")]
+            Error::Overflow{ ref offset, ref size } => {
+                write!(fmt, "offset {} plus size {} overflows", offset, size)
+            },
+            Error::BadOffsetType{ ref offset, ty } => {
+                write!(fmt, "offset {} does not fit in a {}", offset, ty)
+            },
+            Error::BadAlignment{ ref offset, ref align } => {
+                write!(fmt, "offset {} is not aligned to {} bytes", offset, align)
+            },
+            Error::Unfilled{ ref offset, ref count } => {
+                write!(fmt, "{} reserved slots were never filled, the first at offset {}", count, offset)
+            },
+            #[cfg(feature = "alloc")]
             Error::Custom(ref msg) => { write! (fmt, "{}", msg) },
             #[cfg(feature = "std")]
             Error::IO(ref err) => { write!(fmt, "{}", err) },
@@ -72,3 +102,265 @@ impl Display for Error {
 }
 
 pub type Result<T> = result::Result<T, Error>;
+
+/// Adds `size` to `offset`, or returns an `Overflow` error if it wraps
+#[inline]
+pub(crate) fn checked_offset(offset: usize, size: usize) -> Result<usize> {
+    offset.checked_add(size).ok_or(Error::Overflow { offset, size })
+}
+
+/// Checks that `size` bytes at `offset` are within an object of `len` bytes without overflowing, and returns the end of the range
+#[inline]
+pub(crate) fn check_range(offset: usize, size: usize, len: usize) -> Result<usize> {
+    let end = checked_offset(offset, size)?;
+    if end > len {
+        Err(Error::BadRange { range: offset..end, size: len })
+    } else {
+        Ok(end)
+    }
+}
+
+/// The maximum number of frames an `ErrorPath` holds; further, outer, frames are counted but dropped
+pub const MAX_FRAMES: usize = 8;
+
+/// What a `Frame` in an error's `ErrorPath` names
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FrameKind {
+    /// A type being parsed, e.g., `MachHeader`
+    Type,
+    /// A field of the enclosing type, e.g., `cmdsize`
+    Field,
+    /// An element of a table field of the enclosing type, e.g., `load_commands[3]`
+    Element(usize),
+}
+
+/// One level of the parse an error occurred in: the name of the type, field or table being parsed, and the absolute offset it started at
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub name: &'static str,
+    pub kind: FrameKind,
+    pub offset: usize,
+}
+
+impl Frame {
+    /// A frame for the type `name`, parsed at `offset`
+    #[inline]
+    pub fn ty(name: &'static str, offset: usize) -> Self {
+        Frame { name, kind: FrameKind::Type, offset }
+    }
+    /// A frame for the field `name`, parsed at `offset`
+    #[inline]
+    pub fn field(name: &'static str, offset: usize) -> Self {
+        Frame { name, kind: FrameKind::Field, offset }
+    }
+    /// A frame for the `index`th element of the table field `name`, parsed at `offset`
+    #[inline]
+    pub fn element(name: &'static str, index: usize, offset: usize) -> Self {
+        Frame { name, kind: FrameKind::Element(index), offset }
+    }
+}
+
+/// The frames an error propagated up through, innermost first, without allocating; see [ContextError](struct.ContextError.html)
+///
+/// At most `MAX_FRAMES` frames are kept. The innermost ones are the most specific, so once full, outer frames are dropped, but counted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ErrorPath {
+    frames: [Frame; MAX_FRAMES],
+    len: usize,
+    dropped: usize,
+}
+
+impl Default for ErrorPath {
+    fn default() -> Self {
+        ErrorPath { frames: [Frame::ty("", 0); MAX_FRAMES], len: 0, dropped: 0 }
+    }
+}
+
+impl ErrorPath {
+    /// Adds `frame` as the new outermost frame
+    #[inline]
+    pub fn push(&mut self, frame: Frame) {
+        if self.len < MAX_FRAMES {
+            self.frames[self.len] = frame;
+            self.len += 1;
+        } else {
+            self.dropped += 1;
+        }
+    }
+    /// The frames, innermost first
+    #[inline]
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames[..self.len]
+    }
+    /// The number of outer frames which didn't fit
+    #[inline]
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Display for ErrorPath {
+    /// Renders the path outermost first, e.g., `MachHeader.load_commands[3].cmdsize`; type frames are only shown at the root, since fields already name what they contain
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        if self.dropped > 0 {
+            write!(fmt, "..")?;
+            first = false;
+        }
+        for frame in self.frames().iter().rev() {
+            match frame.kind {
+                FrameKind::Type if first => write!(fmt, "{}", frame.name)?,
+                FrameKind::Type => continue,
+                FrameKind::Field if first => write!(fmt, "{}", frame.name)?,
+                FrameKind::Field => write!(fmt, ".{}", frame.name)?,
+                FrameKind::Element(index) if first => write!(fmt, "{}[{}]", frame.name, index)?,
+                FrameKind::Element(index) => write!(fmt, ".{}[{}]", frame.name, index)?,
+            }
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+/// An `Error` along with the `ErrorPath` of types and fields it was encountered in, for debugging deeply nested parsers
+///
+/// Use this as the `Error` of your `TryFromCtx` impls, and add frames with [WithContext::context](trait.WithContext.html#tymethod.context) as errors propagate up through them;
+/// scroll's own errors convert into it with `?`. It doesn't allocate, so it's available without `std`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx, Pread, Endian, Frame, ContextError, WithContext, LE};
+/// struct LoadCommand { cmd: u32, cmdsize: u32 }
+/// impl<'a> ctx::TryFromCtx<'a, (usize, Endian)> for LoadCommand {
+///     type Error = ContextError;
+///     fn try_from_ctx(src: &'a [u8], (offset, le): (usize, Endian)) -> Result<Self, Self::Error> {
+///         let cmd = src.pread_with(offset, le).context(Frame::field("cmd", offset))?;
+///         let cmdsize = src.pread_with(offset + 4, le).context(Frame::field("cmdsize", offset + 4))?;
+///         Ok(LoadCommand { cmd, cmdsize })
+///     }
+/// }
+/// struct MachHeader { ncmds: u32, load_commands: Vec<LoadCommand> }
+/// impl<'a> ctx::TryFromCtx<'a, (usize, Endian)> for MachHeader {
+///     type Error = ContextError;
+///     fn try_from_ctx(src: &'a [u8], (offset, le): (usize, Endian)) -> Result<Self, Self::Error> {
+///         let ncmds: u32 = src.pread_with(offset, le).context(Frame::field("ncmds", offset))?;
+///         let mut load_commands = Vec::new();
+///         let mut o = offset + 4;
+///         for i in 0..ncmds as usize {
+///             let lc: LoadCommand = src.pread_with(o, le).context(Frame::element("load_commands", i, o))?;
+///             o += lc.cmdsize as usize;
+///             load_commands.push(lc);
+///         }
+///         Ok(MachHeader { ncmds, load_commands })
+///     }
+/// }
+/// let mut bytes = [0u8; 0x48];
+/// bytes[0] = 4;
+/// for &(o, cmdsize) in [(0x04, 0x20), (0x24, 0x10), (0x34, 0x10)].iter() {
+///     bytes[o + 4] = cmdsize;
+/// }
+/// let err = bytes.pread_with::<MachHeader>(0, LE).context(Frame::ty("MachHeader", 0)).err().unwrap();
+/// assert_eq!(err.to_string(), "MachHeader.load_commands[3].cmdsize @ 0x48: requested range [0x48..0x4c) from object of len 0x48");
+/// ```
+#[derive(Debug)]
+pub struct ContextError {
+    error: Error,
+    path: ErrorPath,
+}
+
+impl ContextError {
+    /// The underlying error
+    #[inline]
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+    /// The frames the error propagated up through
+    #[inline]
+    pub fn path(&self) -> &ErrorPath {
+        &self.path
+    }
+    /// Consumes self and returns the underlying error, discarding the path
+    #[inline]
+    pub fn into_inner(self) -> Error {
+        self.error
+    }
+}
+
+impl From<Error> for ContextError {
+    #[inline]
+    fn from(error: Error) -> Self {
+        ContextError { error, path: ErrorPath::default() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for ContextError {
+    #[inline]
+    fn from(err: io::Error) -> Self {
+        Error::IO(err).into()
+    }
+}
+
+impl Display for ContextError {
+    /// Renders the path, the absolute offset of the innermost frame, and then the error, with offsets in hex
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(frame) = self.path.frames().first() {
+            write!(fmt, "{} @ {:#x}: ", self.path, frame.offset)?;
+        }
+        match self.error {
+            Error::BadOffset(ref offset) => write!(fmt, "bad offset {:#x}", offset),
+            Error::BadRange{ ref range, ref size } => {
+                write!(fmt, "requested range [{:#x}..{:#x}) from object of len {:#x}", range.start, range.end, size)
+            },
+            Error::BadInput{ ref range, ref size, msg } => {
+                write!(fmt, "{} - range [{:#x}..{:#x}), len {:#x}", msg, range.start, range.end, size)
+            },
+            Error::Overflow{ ref offset, ref size } => {
+                write!(fmt, "offset {:#x} plus size {:#x} overflows", offset, size)
+            },
+            Error::BadOffsetType{ ref offset, ty } => {
+                write!(fmt, "offset {} does not fit in a {}", offset, ty)
+            },
+            Error::BadAlignment{ ref offset, ref align } => {
+                write!(fmt, "offset {:#x} is not aligned to {} bytes", offset, align)
+            },
+            Error::Unfilled{ ref offset, ref count } => {
+                write!(fmt, "{} reserved slots were never filled, the first at offset {:#x}", count, offset)
+            },
+            #[cfg(feature = "alloc")]
+            ref error => write!(fmt, "{}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ContextError {
+    fn description(&self) -> &str {
+        "ContextError"
+    }
+    fn cause(&self) -> Option<&dyn error::Error> {
+        Some(&self.error)
+    }
+}
+
+/// Adds a `Frame` to the error of a `Result` as it propagates up through a parser
+pub trait WithContext<T> {
+    // the path is stored inline so it works without an allocator, which makes the error large
+    #[allow(clippy::result_large_err)]
+    fn context(self, frame: Frame) -> result::Result<T, ContextError>;
+}
+
+impl<T, E: Into<ContextError>> WithContext<T> for result::Result<T, E> {
+    #[inline]
+    fn context(self, frame: Frame) -> result::Result<T, ContextError> {
+        self.map_err(|err| {
+            let mut err = err.into();
+            err.path.push(frame);
+            err
+        })
+    }
+}
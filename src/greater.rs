@@ -3,11 +3,16 @@ use core::result;
 use core::fmt::Debug;
 use core::ops::{Add, AddAssign};
 use core::ops::{Index, IndexMut, RangeFrom};
+use core::marker::PhantomData;
 
-use ctx::{self, TryFromCtx, TryRefFromCtx, TryIntoCtx, FromCtx, IntoCtx, SizeWith};
+use ctx::{self, TryFromCtx, TryReadCtx, TryRefFromCtx, TryWriteCtx, FromCtx, IntoCtx, SizeWith};
 use error::*;
 use error;
-use pread::Pread;
+use pread::{Pread, Offset};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use pread::read_vec;
 use pwrite::Pwrite;
 use endian::Endian;
 
@@ -24,11 +29,15 @@ pub trait TryOffsetWith<Ctx = ctx::DefaultCtx, E = error::Error, I = usize> {
 /// `Gread` implements an immutable `Self`, `mutable` reference offset incrementor which uses `Pread` as its base.
 /// If you are writing a custom `Gread` interface,
 /// you should only need to implement `Pread` for a particular
-/// `Ctx`, `Error`, `Index` target, _and_ implement `TryOffsetWith` to explain to the trait how it should increment the mutable offset,
+/// `Ctx`, `Error`, `Index` target, _and_ implement `TryOffsetWith`,
 /// and then a simple blanket `impl Gread<I, E, Ctx> for YourType`, etc.
+/// Reads go through [pread_sized_with](trait.Pread.html#method.pread_sized_with), and the offset is incremented by the size it reports.
+///
+/// The offset `I` can be any [Offset](trait.Offset.html), e.g., a `Pread<Ctx, E, u64, (usize, Ctx)>` indexed by 64-bit file offsets also gets a `Gread`;
+/// the incremented offset must fit back in an `I`, otherwise it is a `BadOffsetType`.
 pub trait Gread<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), SliceCtx = (I, I, Ctx)> : Pread<Ctx, E, I, TryCtx, SliceCtx> + TryOffsetWith<Ctx, E, I>
     where Ctx: Copy + Default + Debug,
-          I: AddAssign + Copy + Add + Default + Debug,
+          I: Offset + AddAssign + Copy + Add + Default + Debug,
           E: Debug,
           TryCtx: Copy + Default + Debug,
           SliceCtx: Copy + Default + Debug,
@@ -36,11 +45,8 @@ pub trait Gread<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), Sl
     #[inline]
     /// Reads _and_ unwraps a value from `self` at `offset` with the given `ctx`. **NB**: this can panic if the offset is bad, or whatever error this operates on is "thrown".
     /// For the primitive numeric values, this will read at the machine's endianness. Updates the offset
-    fn gread_unsafe<'a, N: SizeWith<Ctx, Units = I> + TryFromCtx<'a, TryCtx, Error = E>>(&'a self, offset: &mut I, ctx: Ctx) -> N {
-        let o = *offset;
-        let count = self.try_offset::<N>(o, &ctx).unwrap();
-        *offset += count;
-        self.pread_unsafe(o, ctx)
+    fn gread_unsafe<'a, N: TryReadCtx<'a, TryCtx, Error = E>>(&'a self, offset: &mut I, ctx: Ctx) -> N where E: From<error::Error> {
+        self.gread_with(offset, ctx).unwrap()
     }
     #[inline]
     /// Reads a value from `self` at `offset` with a default `Ctx`. For the primitive numeric values, this will read at the machine's endianness. Updates the offset
@@ -51,11 +57,14 @@ pub trait Gread<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), Sl
     /// let bytes = [0x7fu8; 0x01];
     /// let byte = bytes.gread::<u8>(offset).unwrap();
     /// assert_eq!(*offset, 1);
-    fn gread<'a, N: SizeWith<Ctx, Units = I> + TryFromCtx<'a, TryCtx, Error = E>>(&'a self, offset: &mut I) -> result::Result<N, E> {
+    fn gread<'a, N: TryReadCtx<'a, TryCtx, Error = E>>(&'a self, offset: &mut I) -> result::Result<N, E> where E: From<error::Error> {
         let ctx = Ctx::default();
         self.gread_with(offset, ctx)
     }
-    /// Reads a value from `self` at `offset` with the given `ctx`, and updates the offset.
+    /// Reads a value from `self` at `offset` with the given `ctx`, and advances the offset by however many bytes the value consumed.
+    ///
+    /// `N` can be variable length, e.g., a `Uleb128`, or a `&str` with a `StrCtx`; anything implementing `TryFromCtx` and `SizeWith` works too.
+    /// On error the offset is left untouched.
     /// # Example
     /// ```rust
     /// use scroll::Gread;
@@ -64,13 +73,58 @@ pub trait Gread<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), Sl
     /// let dead: u16 = bytes.gread_with(offset, scroll::BE).unwrap();
     /// assert_eq!(dead, 0xdeadu16);
     /// assert_eq!(*offset, 2);
+    /// ```
+    ///
+    /// ```rust
+    /// use scroll::{Gread, Uleb128, LE, ctx};
+    /// let bytes = [b'h', b'i', 0, 0xe5, 0x8e, 0x26, 0xef, 0xbe, 0xad, 0xde];
+    /// let offset = &mut 0;
+    /// let name: &str = bytes.gread_with(offset, ctx::NULL).unwrap();
+    /// let size: Uleb128 = bytes.gread(offset).unwrap();
+    /// let magic: u32 = bytes.gread_with(offset, LE).unwrap();
+    /// assert_eq!((name, u64::from(size), magic), ("hi", 624485, 0xdeadbeef));
+    /// assert_eq!(*offset, 10);
+    /// ```
     #[inline]
-    fn gread_with<'a, N: SizeWith<Ctx, Units = I> + TryFromCtx<'a, TryCtx, Error = E>>(&'a self, offset: &mut I, ctx: Ctx) -> result::Result<N, E> {
+    fn gread_with<'a, N: TryReadCtx<'a, TryCtx, Error = E>>(&'a self, offset: &mut I, ctx: Ctx) -> result::Result<N, E> where E: From<error::Error> {
         let o = *offset;
-        let count = self.try_offset::<N>(o, &ctx)?;
-        let res = self.pread_unsafe(o, ctx);
-        *offset += count;
-        Ok(res)
+        let (n, size) = self.pread_sized_with(o, ctx)?;
+        *offset = I::try_from_index(error::checked_offset(o.try_into_index()?, size)?)?;
+        Ok(n)
+    }
+    /// Reads a value from `self` at an `offset` of any [Offset](trait.Offset.html) type, e.g., a `u32`, with a default `Ctx`, and updates the offset
+    #[inline]
+    fn gread_offset<'a, N, O>(&'a self, offset: &mut O) -> result::Result<N, E>
+        where N: TryReadCtx<'a, TryCtx, Error = E>,
+              O: Offset,
+              E: From<error::Error> {
+        self.gread_offset_with(offset, Ctx::default())
+    }
+    /// Reads a value from `self` at an `offset` of any [Offset](trait.Offset.html) type, e.g., a `u32`, with the given `ctx`, and updates the offset
+    ///
+    /// An offset which doesn't fit in a `usize`, or a new offset which doesn't fit back in an `O`, is a `BadOffsetType`, and the offset is left untouched.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Gread, Error, BE};
+    /// let bytes = [0u8; 300];
+    /// let offset = &mut 253u8;
+    /// let half: u16 = bytes.gread_offset_with(offset, BE).unwrap();
+    /// assert_eq!((half, *offset), (0, 0xff));
+    /// match bytes.gread_offset_with::<u16, _>(offset, BE) {
+    ///     Err(Error::BadOffsetType { offset: 257, ty: "u8" }) => (),
+    ///     res => panic!("expected a BadOffsetType, got {:?}", res),
+    /// }
+    /// assert_eq!(*offset, 0xff);
+    /// ```
+    #[inline]
+    fn gread_offset_with<'a, N, O>(&'a self, offset: &mut O, ctx: Ctx) -> result::Result<N, E>
+        where N: TryReadCtx<'a, TryCtx, Error = E>,
+              O: Offset,
+              E: From<error::Error> {
+        let mut o = I::try_from_index(offset.try_into_index()?)?;
+        let n = self.gread_with(&mut o, ctx)?;
+        *offset = O::try_from_index(o.try_into_index()?)?;
+        Ok(n)
     }
     /// Slices an `N` from `self` at `offset` up to `count` times, and updates the offset.
     /// # Example
@@ -102,13 +156,10 @@ pub trait Gread<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), Sl
     #[inline]
     fn gread_inout<'a, N>(&'a self, offset: &mut I, inout: &mut [N]) -> result::Result<(), E>
         where
-        N: SizeWith<Ctx, Units = I> + TryFromCtx<'a, TryCtx, Error = E>,
+        N: TryReadCtx<'a, TryCtx, Error = E>,
+        E: From<error::Error>,
     {
-        let len = inout.len();
-        for i in 0..len {
-            inout[i] = self.gread(offset)?;
-        }
-        Ok(())
+        self.gread_inout_with(offset, inout, Ctx::default())
     }
 
     /// Trys to write `inout.len()` `N`s into `inout` from `Self` starting at `offset`, using the context `ctx`
@@ -124,7 +175,8 @@ pub trait Gread<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), Sl
     #[inline]
     fn gread_inout_with<'a, N>(&'a self, offset: &mut I, inout: &mut [N], ctx: Ctx) -> result::Result<(), E>
         where
-        N: SizeWith<Ctx, Units = I> + TryFromCtx<'a, TryCtx, Error = E>,
+        N: TryReadCtx<'a, TryCtx, Error = E>,
+        E: From<error::Error>,
     {
         let len = inout.len();
         for i in 0..len {
@@ -132,17 +184,60 @@ pub trait Gread<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), Sl
         }
         Ok(())
     }
+    /// Reads `count` `N`s from `self` starting at `offset` with a default `Ctx` into a `Vec`, and updates the offset past the last one. Only present when `alloc` feature is used (the default).
+    /// # Example
+    /// ```rust
+    /// use scroll::{Gread, Uleb128};
+    /// let bytes = [0xe5, 0x8e, 0x26, 0x7f];
+    /// let offset = &mut 0;
+    /// let ulebs: Vec<Uleb128> = bytes.gread_vec(offset, 2).unwrap();
+    /// assert_eq!(ulebs, [Uleb128::new(624485), Uleb128::new(0x7f)]);
+    /// assert_eq!(*offset, 4);
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn gread_vec<'a, N>(&'a self, offset: &mut I, count: usize) -> result::Result<Vec<N>, E>
+        where Self: AsRef<[u8]>,
+              N: TryReadCtx<'a, (usize, Ctx), Error = E>,
+              E: From<error::Error> {
+        self.gread_vec_with(offset, count, Ctx::default())
+    }
+    /// Reads `count` `N`s from `self` starting at `offset` with `ctx` into a `Vec`, and updates the offset past the last one. Only present when `alloc` feature is used (the default).
+    ///
+    /// `N` can be variable length, e.g., a `Uleb128` or a `&str`, in which case the offset is advanced by however many bytes each item consumed.
+    /// If `N` has a fixed size, like the primitives or anything implementing `SizeWith`, the whole table is bounds checked before anything is read.
+    /// On error the offset is left untouched.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Gread, LE};
+    /// // e.g., an ELF `e_phnum` followed by that many `p_type`s
+    /// let bytes = [2, 0, 1, 0, 0, 0, 3, 0, 0, 0];
+    /// let offset = &mut 0;
+    /// let phnum: u16 = bytes.gread_with(offset, LE).unwrap();
+    /// let p_types: Vec<u32> = bytes.gread_vec_with(offset, phnum as usize, LE).unwrap();
+    /// assert_eq!(p_types, [1, 3]);
+    /// assert_eq!(*offset, 10);
+    /// assert!(bytes.gread_vec_with::<u32>(&mut 2, 3, LE).is_err());
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn gread_vec_with<'a, N>(&'a self, offset: &mut I, count: usize, ctx: Ctx) -> result::Result<Vec<N>, E>
+        where Self: AsRef<[u8]>,
+              N: TryReadCtx<'a, (usize, Ctx), Error = E>,
+              E: From<error::Error> {
+        let mut o = (*offset).try_into_index()?;
+        let vec = read_vec::<Ctx, N, E>(self.as_ref(), &mut o, count, ctx)?;
+        *offset = I::try_from_index(o)?;
+        Ok(vec)
+    }
 }
 
 impl<Ctx> TryOffsetWith<Ctx> for [u8] {
     #[inline]
     fn try_offset<N: SizeWith<Ctx, Units = usize>>(&self, offset: usize, ctx: &Ctx) -> Result<usize> {
         let size = N::size_with(ctx);
-        if offset + size > self.len() {
-            Err(error::Error::BadRange{range: offset..offset+size, size: self.len()})
-        } else {
-            Ok(size)
-        }
+        error::check_range(offset, size, self.len())?;
+        Ok(size)
     }
 }
 
@@ -173,34 +268,237 @@ impl<Ctx, E, T> Gread<Ctx, E> for T where
 //     }
 // }
 
+/// A lazy table of `T`s in a byte slice, which reads each element on demand with `ctx`, without allocating
+///
+/// A table either has a known number of elements, e.g., ELF's `e_phnum`, or runs up to an end offset, e.g., a section's size.
+/// Elements can be variable length, like a `Uleb128` or a `&str`, in which case each one starts where the previous one ended.
+/// Iteration yields `Result`s, and stops after the first error.
+///
+/// Fixed size elements (anything implementing `SizeWith`, like the primitives) additionally get `len`, random access with `get`, and iteration from the back.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx, Table, BE};
+/// let bytes = [0xde, 0xad, 0xbe, 0xef, 0x00, 0x01, b'h', b'i', 0, b'y', b'o', 0];
+/// let table: Table<u16> = Table::new(&bytes, 0, 3, BE);
+/// assert_eq!(table.len(), 3);
+/// assert_eq!(table.get(1).unwrap().unwrap(), 0xbeef);
+/// assert_eq!(table.rev().map(Result::unwrap).collect::<Vec<_>>(), [0x0001, 0xbeef, 0xdead]);
+/// // a string table running to the end of the bytes
+/// let strings = Table::<&str, _>::with_end(&bytes, 6, bytes.len(), ctx::NULL);
+/// assert_eq!(strings.map(Result::unwrap).collect::<Vec<_>>(), ["hi", "yo"]);
+/// // the bytes only hold 6 `u16`s, so the 7th is an error, after which the table is exhausted
+/// let mut table: Table<u16> = Table::new(&bytes, 0, 10, BE);
+/// assert!(table.by_ref().take(6).all(|n| n.is_ok()));
+/// assert!(table.next().unwrap().is_err());
+/// assert!(table.next().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Table<'a, T, Ctx = ctx::DefaultCtx> {
+    bytes: &'a [u8],
+    offset: usize,
+    end: usize,
+    count: Option<usize>,
+    ctx: Ctx,
+    done: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T, Ctx: Copy> Table<'a, T, Ctx> {
+    /// Creates a table of `count` `T`s in `bytes` starting at `offset`
+    #[inline]
+    pub fn new(bytes: &'a [u8], offset: usize, count: usize, ctx: Ctx) -> Self {
+        Table { bytes, offset, end: bytes.len(), count: Some(count), ctx, done: false, _marker: PhantomData }
+    }
+    /// Creates a table of `T`s in `bytes` from `offset` up to, but not including, `end`; elements can't read past `end`
+    #[inline]
+    pub fn with_end(bytes: &'a [u8], offset: usize, end: usize, ctx: Ctx) -> Self {
+        Table { bytes, offset, end, count: None, ctx, done: false, _marker: PhantomData }
+    }
+    /// The offset of the next element in `bytes`; once the table is exhausted, this is the offset just past it, e.g., to continue `gread`ing from
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    /// The bytes elements are read from, which end at the table's end offset, if it has one
+    #[inline]
+    fn src(&self) -> &'a [u8] {
+        let bytes = self.bytes;
+        if self.end < bytes.len() { &bytes[..self.end] } else { bytes }
+    }
+    #[inline]
+    fn is_finished(&self) -> bool {
+        self.done || match self.count {
+            Some(count) => count == 0,
+            None => self.offset >= self.end,
+        }
+    }
+}
+
+impl<'a, T, Ctx> Table<'a, T, Ctx> where Ctx: Copy, T: TryFromCtx<'a, (usize, Ctx)> + SizeWith<Ctx, Units = usize> {
+    /// The number of elements remaining; a trailing partial element in a table with an end offset counts too, since reading it is an error
+    #[inline]
+    pub fn len(&self) -> usize {
+        if self.done { return 0 }
+        match self.count {
+            Some(count) => count,
+            None => {
+                let size = T::size_with(&self.ctx);
+                let remaining = self.end.saturating_sub(self.offset);
+                if size == 0 { 0 } else { remaining.div_ceil(size) }
+            }
+        }
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Reads the `index`th remaining element, or returns `None` if it is out of bounds; this doesn't advance the table
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<result::Result<T, T::Error>> {
+        if index >= self.len() {
+            return None
+        }
+        // `count` is untrusted, so saturate and let the element's own bounds check fail instead of wrapping
+        let offset = self.offset.saturating_add(index.saturating_mul(T::size_with(&self.ctx)));
+        Some(T::try_from_ctx(self.src(), (offset, self.ctx)))
+    }
+}
+
+impl<'a, T, Ctx> Iterator for Table<'a, T, Ctx> where Ctx: Copy, T: TryReadCtx<'a, (usize, Ctx)> {
+    type Item = result::Result<T, T::Error>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished() {
+            return None
+        }
+        match T::try_read_ctx(self.src(), (self.offset, self.ctx)) {
+            Ok((n, size)) => {
+                self.offset = self.offset.saturating_add(size);
+                match self.count {
+                    Some(ref mut count) => *count -= 1,
+                    // an element which consumes nothing would otherwise repeat forever
+                    None => self.done = size == 0,
+                }
+                Some(Ok(n))
+            },
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.is_finished() {
+            (0, Some(0))
+        } else {
+            (1, self.count)
+        }
+    }
+}
+
+impl<'a, T, Ctx> DoubleEndedIterator for Table<'a, T, Ctx>
+    where Ctx: Copy,
+          T: TryFromCtx<'a, (usize, Ctx)> + SizeWith<Ctx, Units = usize>,
+          <T as TryFromCtx<'a, (usize, Ctx)>>::Error: From<error::Error> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let len = self.len();
+        if len == 0 {
+            return None
+        }
+        let offset = self.offset.saturating_add((len - 1).saturating_mul(T::size_with(&self.ctx)));
+        match T::try_from_ctx(self.src(), (offset, self.ctx)) {
+            Ok(n) => {
+                match self.count {
+                    Some(ref mut count) => *count -= 1,
+                    None => self.end = offset,
+                }
+                Some(Ok(n))
+            },
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
 /// The Greater Write (`Gwrite`) writes a value into its mutable insides, at a mutable offset
+///
+/// Writes go through [pwrite_sized_with](trait.Pwrite.html#method.pwrite_sized_with), and the offset is incremented by the size it reports.
+/// As with `Gread`, the offset `I` can be any [Offset](trait.Offset.html), and the incremented offset must fit back in an `I`.
 pub trait Gwrite<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), SliceCtx = (I, I, Ctx)>: Pwrite<Ctx, E, I, TryCtx, SliceCtx> + TryOffsetWith<Ctx, E, I>
  where E: Debug,
        Ctx: Copy + Default + Debug,
-       I: AddAssign + Copy + Add + Default + Debug,
+       I: Offset + AddAssign + Copy + Add + Default + Debug,
        TryCtx: Copy + Default + Debug,
        SliceCtx: Copy + Default + Debug,
 {
     #[inline]
-    fn gwrite_unsafe<N: SizeWith<Ctx, Units = I> + TryIntoCtx<TryCtx, Error = E>>(&mut self, n: N, offset: &mut I, ctx: Ctx) {
-        let o = *offset;
-        let count = self.try_offset::<N>(o, &ctx).unwrap();
-        *offset += count;
-        self.pwrite_unsafe(n, o, ctx)
+    fn gwrite_unsafe<N: TryWriteCtx<TryCtx, Error = E>>(&mut self, n: N, offset: &mut I, ctx: Ctx) where E: From<error::Error> {
+        self.gwrite_with(n, offset, ctx).unwrap()
     }
     /// Write `n` into `self` at `offset`, with a default `Ctx`. Updates the offset.
     #[inline]
-    fn gwrite<N: SizeWith<Ctx, Units = I> + TryIntoCtx<TryCtx, Error = E>>(&mut self, n: N, offset: &mut I) -> result::Result<(), E> {
+    fn gwrite<N: TryWriteCtx<TryCtx, Error = E>>(&mut self, n: N, offset: &mut I) -> result::Result<(), E> where E: From<error::Error> {
         let ctx = Ctx::default();
         self.gwrite_with(n, offset, ctx)
     }
-    /// Write `n` into `self` at `offset`, with the `ctx`. Updates the offset.
+    /// Write `n` into `self` at `offset`, with the `ctx`, and advances the offset by however many bytes `n` wrote.
+    ///
+    /// `N` can be variable length, e.g., a `Uleb128`, a `&[u8]` or a `&str`; anything implementing `TryIntoCtx` and `SizeWith` works too.
+    /// On error the offset is left untouched.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Gwrite, Uleb128, LE, ctx};
+    /// let mut bytes = [0u8; 12];
+    /// let offset = &mut 0;
+    /// bytes.gwrite_with(Uleb128::new(300), offset, LE).unwrap();
+    /// bytes.gwrite_with("hi", offset, ctx::NULL).unwrap();
+    /// bytes.gwrite_with(&b"raw"[..], offset, LE).unwrap();
+    /// bytes.gwrite_with(0xbeefu16, offset, LE).unwrap();
+    /// assert_eq!(*offset, 10);
+    /// assert_eq!(bytes[..10], [0xac, 0x02, b'h', b'i', 0, b'r', b'a', b'w', 0xef, 0xbe]);
+    /// ```
     #[inline]
-    fn gwrite_with<N: SizeWith<Ctx, Units = I> + TryIntoCtx<TryCtx, Error = E>>(&mut self, n: N, offset: &mut I, ctx: Ctx) -> result::Result<(), E> {
+    fn gwrite_with<N: TryWriteCtx<TryCtx, Error = E>>(&mut self, n: N, offset: &mut I, ctx: Ctx) -> result::Result<(), E> where E: From<error::Error> {
         let o = *offset;
-        let count = self.try_offset::<N>(o, &ctx)?;
-        *offset += count;
-        self.pwrite_unsafe(n, o, ctx);
+        let size = self.pwrite_sized_with(n, o, ctx)?;
+        *offset = I::try_from_index(error::checked_offset(o.try_into_index()?, size)?)?;
+        Ok(())
+    }
+    /// Write `n` into `self` at an `offset` of any [Offset](trait.Offset.html) type, e.g., a `u32`, with a default `Ctx`, and updates the offset
+    #[inline]
+    fn gwrite_offset<N, O>(&mut self, n: N, offset: &mut O) -> result::Result<(), E>
+        where N: TryWriteCtx<TryCtx, Error = E>,
+              O: Offset,
+              E: From<error::Error> {
+        self.gwrite_offset_with(n, offset, Ctx::default())
+    }
+    /// Write `n` into `self` at an `offset` of any [Offset](trait.Offset.html) type, e.g., a `u32`, with the `ctx`, and updates the offset
+    ///
+    /// An offset which doesn't fit in a `usize` is a `BadOffsetType`, and nothing is written.
+    /// A new offset which doesn't fit back in an `O` is only found after writing; it is a `BadOffsetType` too, and the offset is left untouched.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Gwrite, LE};
+    /// let mut bytes = [0u8; 8];
+    /// let offset = &mut 2u16;
+    /// bytes.gwrite_offset_with(0xbeefu16, offset, LE).unwrap();
+    /// bytes.gwrite_offset_with(0xffu8, offset, LE).unwrap();
+    /// assert_eq!(*offset, 5);
+    /// assert_eq!(bytes[..6], [0, 0, 0xef, 0xbe, 0xff, 0]);
+    /// ```
+    #[inline]
+    fn gwrite_offset_with<N, O>(&mut self, n: N, offset: &mut O, ctx: Ctx) -> result::Result<(), E>
+        where N: TryWriteCtx<TryCtx, Error = E>,
+              O: Offset,
+              E: From<error::Error> {
+        let mut o = I::try_from_index(offset.try_into_index()?)?;
+        self.gwrite_with(n, &mut o, ctx)?;
+        *offset = O::try_from_index(o.try_into_index()?)?;
         Ok(())
     }
 }
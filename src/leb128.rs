@@ -1,8 +1,12 @@
 use core::u8;
 use core::convert::{From, AsRef};
 
-use ctx::{self, TryFromCtx};
+use ctx::{self, TryFromCtx, TryReadCtx, TryIntoCtx, TryWriteCtx};
+#[cfg(feature = "std")]
+use ctx::{TryFromStream, TryIntoStream};
 use error;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
 /// A variable length integer parsing `Ctx`, compatible with the standard integer endian-aware parsing context
 pub type Leb128 = ctx::DefaultCtx;
@@ -20,6 +24,27 @@ pub struct Uleb128 {
 }
 
 impl Uleb128 {
+    #[inline]
+    /// Create a new `Uleb128` from `value`, which will be encoded in the fewest bytes possible
+    pub fn new(value: u64) -> Self {
+        Uleb128 { value, count: uleb128_size(value) }
+    }
+    #[inline]
+    /// Create a new `Uleb128` from `value` which will be encoded in exactly `width` bytes, padding with continuation bytes as necessary.
+    ///
+    /// This is useful for fixed-width fields which are patched later, e.g., the 5 byte ULEBs in WebAssembly relocations.
+    /// Errors if `value` does not fit in `width` bytes, or `width` is larger than the maximum length of a 64-bit leb128.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pwrite, Uleb128};
+    /// let mut bytes = [0u8; 5];
+    /// bytes.pwrite(Uleb128::padded(2, 5).unwrap(), 0).unwrap();
+    /// assert_eq!(bytes, [0x82, 0x80, 0x80, 0x80, 0x00]);
+    /// ```
+    pub fn padded(value: u64, width: usize) -> error::Result<Self> {
+        check_width(uleb128_size(value), width)?;
+        Ok(Uleb128 { value, count: width })
+    }
     #[inline]
     /// Return how many bytes this Uleb128 takes up in memory
     pub fn size(&self) -> usize {
@@ -28,11 +53,36 @@ impl Uleb128 {
     #[inline]
     /// Read a variable length u64 from `bytes` at `offset`
     pub fn read<B: AsRef<[u8]>>(bytes: &B, offset: &mut usize) -> error::Result<u64> {
-        use Pread;
-        let tmp = bytes.pread::<Uleb128>(*offset)?;
-        *offset = *offset + tmp.size();
+        use Gread;
+        let tmp: Uleb128 = bytes.as_ref().gread(offset)?;
         Ok(tmp.into())
     }
+    #[inline]
+    /// Write `value` as a variable length u64 into `bytes` at `offset`, and increment the offset by the number of bytes written
+    /// # Example
+    /// ```rust
+    /// use scroll::Uleb128;
+    /// let mut bytes = [0u8; 4];
+    /// let offset = &mut 0;
+    /// Uleb128::write(&mut bytes, offset, 624485).unwrap();
+    /// assert_eq!(*offset, 3);
+    /// assert_eq!(Uleb128::read(&bytes, &mut 0).unwrap(), 624485);
+    /// ```
+    pub fn write<B: AsMut<[u8]>>(bytes: &mut B, offset: &mut usize, value: u64) -> error::Result<usize> {
+        use Pwrite;
+        let tmp = Uleb128::new(value);
+        let size = tmp.size();
+        bytes.pwrite(tmp, *offset)?;
+        *offset = error::checked_offset(*offset, size)?;
+        Ok(size)
+    }
+}
+
+impl From<u64> for Uleb128 {
+    #[inline]
+    fn from(value: u64) -> Self {
+        Uleb128::new(value)
+    }
 }
 
 impl AsRef<u64> for Uleb128 {
@@ -56,6 +106,19 @@ pub struct Sleb128 {
 }
 
 impl Sleb128 {
+    #[inline]
+    /// Create a new `Sleb128` from `value`, which will be encoded in the fewest bytes possible
+    pub fn new(value: i64) -> Self {
+        Sleb128 { value, count: sleb128_size(value) }
+    }
+    #[inline]
+    /// Create a new `Sleb128` from `value` which will be encoded in exactly `width` bytes, padding with sign-extended continuation bytes as necessary.
+    ///
+    /// Errors if `value` does not fit in `width` bytes, or `width` is larger than the maximum length of a 64-bit leb128.
+    pub fn padded(value: i64, width: usize) -> error::Result<Self> {
+        check_width(sleb128_size(value), width)?;
+        Ok(Sleb128 { value, count: width })
+    }
     #[inline]
     /// Return how many bytes this Sleb128 takes up in memory
     pub fn size(&self) -> usize {
@@ -64,11 +127,27 @@ impl Sleb128 {
     #[inline]
     /// Read a variable length i64 from `bytes` at `offset`
     pub fn read<B: AsRef<[u8]>>(bytes: &B, offset: &mut usize) -> error::Result<i64> {
-        use Pread;
-        let tmp = bytes.pread::<Sleb128>(*offset)?;
-        *offset = *offset + tmp.size();
+        use Gread;
+        let tmp: Sleb128 = bytes.as_ref().gread(offset)?;
         Ok(tmp.into())
     }
+    #[inline]
+    /// Write `value` as a variable length i64 into `bytes` at `offset`, and increment the offset by the number of bytes written
+    pub fn write<B: AsMut<[u8]>>(bytes: &mut B, offset: &mut usize, value: i64) -> error::Result<usize> {
+        use Pwrite;
+        let tmp = Sleb128::new(value);
+        let size = tmp.size();
+        bytes.pwrite(tmp, *offset)?;
+        *offset = error::checked_offset(*offset, size)?;
+        Ok(size)
+    }
+}
+
+impl From<i64> for Sleb128 {
+    #[inline]
+    fn from(value: i64) -> Self {
+        Sleb128::new(value)
+    }
 }
 
 impl AsRef<i64> for Sleb128 {
@@ -88,11 +167,47 @@ impl From<Sleb128> for i64 {
 const CONTINUATION_BIT: u8 = 1 << 7;
 const SIGN_BIT: u8 = 1 << 6;
 
+/// The maximum number of bytes a 64-bit leb128 can occupy
+const MAX_SIZE: usize = 10;
+
 #[inline]
 fn mask_continuation(byte: u8) -> u8 {
     byte & !CONTINUATION_BIT
 }
 
+#[inline]
+fn uleb128_size(mut value: u64) -> usize {
+    let mut count = 1;
+    value >>= 7;
+    while value != 0 {
+        value >>= 7;
+        count += 1;
+    }
+    count
+}
+
+#[inline]
+fn sleb128_size(mut value: i64) -> usize {
+    let mut count = 0;
+    loop {
+        let byte = value as u8;
+        value >>= 7;
+        count += 1;
+        if (value == 0 && byte & SIGN_BIT == 0) || (value == -1 && byte & SIGN_BIT != 0) {
+            return count;
+        }
+    }
+}
+
+#[inline]
+fn check_width(size: usize, width: usize) -> error::Result<()> {
+    if width < size || width > MAX_SIZE {
+        Err(error::Error::BadInput{ range: 0..width, size, msg: "leb128 width cannot hold value"})
+    } else {
+        Ok(())
+    }
+}
+
 // #[inline]
 // fn mask_continuation_u64(val: u64) -> u8 {
 //     let byte = val & (u8::MAX as u64);
@@ -108,7 +223,7 @@ impl<'a> TryFromCtx<'a, (usize, Leb128)> for Uleb128 {
         let mut shift = 0;
         let mut count = 0;
         loop {
-            let byte: u8 = src.pread(offset + count)?;
+            let byte: u8 = src.pread(error::checked_offset(offset, count)?)?;
 
             if shift == 63 && byte != 0x00 && byte != 0x01 {
                 return Err(error::Error::BadInput{ range: offset..offset+count, size: src.len(), msg: "failed to parse"})
@@ -162,6 +277,143 @@ impl<'a> TryFromCtx<'a, (usize, Leb128)> for Sleb128 {
     }
 }
 
+impl<'a> TryReadCtx<'a, (usize, Leb128)> for Uleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_read_ctx(src: &'a [u8], ctx: (usize, Leb128)) -> error::Result<(Self, usize)> {
+        let uleb = Uleb128::try_from_ctx(src, ctx)?;
+        Ok((uleb, uleb.count))
+    }
+}
+
+impl<'a> TryReadCtx<'a, (usize, Leb128)> for Sleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_read_ctx(src: &'a [u8], ctx: (usize, Leb128)) -> error::Result<(Self, usize)> {
+        let sleb = Sleb128::try_from_ctx(src, ctx)?;
+        Ok((sleb, sleb.count))
+    }
+}
+
+impl TryIntoCtx<(usize, Leb128)> for Uleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], (offset, _ctx): (usize, Leb128)) -> error::Result<()> {
+        let count = self.count;
+        let end = error::check_range(offset, count, dst.len())?;
+        let mut value = self.value;
+        for (i, byte) in dst[offset..end].iter_mut().enumerate() {
+            *byte = mask_continuation(value as u8);
+            value >>= 7;
+            if i + 1 < count {
+                *byte |= CONTINUATION_BIT;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryWriteCtx<(usize, Leb128)> for Uleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_write_ctx(self, dst: &mut [u8], ctx: (usize, Leb128)) -> error::Result<usize> {
+        let count = self.count;
+        self.try_into_ctx(dst, ctx)?;
+        Ok(count)
+    }
+}
+
+impl TryIntoCtx<(usize, Leb128)> for Sleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], (offset, _ctx): (usize, Leb128)) -> error::Result<()> {
+        let count = self.count;
+        let end = error::check_range(offset, count, dst.len())?;
+        let mut value = self.value;
+        for (i, byte) in dst[offset..end].iter_mut().enumerate() {
+            *byte = mask_continuation(value as u8);
+            // arithmetic shift, so padding bytes are sign extended
+            value >>= 7;
+            if i + 1 < count {
+                *byte |= CONTINUATION_BIT;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryWriteCtx<(usize, Leb128)> for Sleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_write_ctx(self, dst: &mut [u8], ctx: (usize, Leb128)) -> error::Result<usize> {
+        let count = self.count;
+        self.try_into_ctx(dst, ctx)?;
+        Ok(count)
+    }
+}
+
+/// Reads the bytes of a leb128 one at a time, up to and including the byte without a continuation bit, and parses them with `N`'s `TryFromCtx`
+#[cfg(feature = "std")]
+fn read_leb128_bytes<R: Read + ?Sized, N: for<'a> TryFromCtx<'a, (usize, Leb128), Error = error::Error>>(src: &mut R) -> error::Result<N> {
+    use pread::Pread;
+    let mut bytes = [0u8; MAX_SIZE];
+    let mut count = 0;
+    loop {
+        if count == MAX_SIZE {
+            return Err(error::Error::BadInput{ range: 0..count, size: count, msg: "failed to parse"})
+        }
+        src.read_exact(&mut bytes[count..count+1])?;
+        count += 1;
+        if bytes[count-1] & CONTINUATION_BIT == 0 {
+            return bytes[..count].pread(0)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFromStream<Leb128> for Uleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_stream<R: Read + ?Sized>(src: &mut R, _ctx: Leb128) -> error::Result<Self> {
+        read_leb128_bytes(src)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFromStream<Leb128> for Sleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_from_stream<R: Read + ?Sized>(src: &mut R, _ctx: Leb128) -> error::Result<Self> {
+        read_leb128_bytes(src)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryIntoStream<Leb128> for Uleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_stream<W: Write + ?Sized>(self, dst: &mut W, ctx: Leb128) -> error::Result<()> {
+        let mut bytes = [0u8; MAX_SIZE];
+        let size = self.size();
+        self.try_into_ctx(&mut bytes, (0, ctx))?;
+        dst.write_all(&bytes[..size])?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryIntoStream<Leb128> for Sleb128 {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_stream<W: Write + ?Sized>(self, dst: &mut W, ctx: Leb128) -> error::Result<()> {
+        let mut bytes = [0u8; MAX_SIZE];
+        let size = self.size();
+        self.try_into_ctx(&mut bytes, (0, ctx))?;
+        dst.write_all(&bytes[..size])?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{LEB128, Uleb128, Sleb128};
@@ -230,4 +482,77 @@ mod tests {
         let num: i64 = bytes.pread::<Sleb128>(0).expect("Should read Sleb128").into();
         assert_eq!(-129, num);
     }
+
+    #[test]
+    fn uleb128_roundtrip() {
+        use super::super::{Pread, Pwrite};
+        let values = [0u64, 1, 0x7f, 0x80, 130, 624485, 0xffff_ffff, ::core::u64::MAX];
+        let sizes = [1usize, 1, 1, 2, 2, 3, 5, 10];
+        let mut buf = [0u8; 10];
+        for (&value, &size) in values.iter().zip(sizes.iter()) {
+            let uleb = Uleb128::new(value);
+            assert_eq!(uleb.size(), size);
+            buf.pwrite(uleb, 0).unwrap();
+            let num = buf.pread::<Uleb128>(0).unwrap();
+            assert_eq!(num, uleb);
+            assert_eq!(value, num.into());
+        }
+        let mut buf = [0u8; 3];
+        assert!(buf.pwrite(Uleb128::new(624485), 1).is_err());
+        buf.pwrite(Uleb128::from(624485), 0).unwrap();
+        assert_eq!(buf, [0xe5, 0x8e, 0x26]);
+    }
+
+    #[test]
+    fn uleb128_padded() {
+        use super::super::{Pread, Pwrite};
+        let mut buf = [0u8; 10];
+        for width in 1..11 {
+            let uleb = Uleb128::padded(0x7f, width).unwrap();
+            assert_eq!(uleb.size(), width);
+            buf.pwrite(uleb, 0).unwrap();
+            let num = buf.pread::<Uleb128>(0).unwrap();
+            assert_eq!(num.size(), width);
+            assert_eq!(0x7fu64, num.into());
+        }
+        assert!(Uleb128::padded(0x80, 1).is_err());
+        assert!(Uleb128::padded(0, 11).is_err());
+        assert!(Uleb128::padded(::core::u64::MAX, 10).is_ok());
+    }
+
+    #[test]
+    fn sleb128_roundtrip() {
+        use super::super::{Pread, Pwrite};
+        let values = [0i64, 1, -1, 63, 64, -64, -65, -129, 0x7fff_ffff, ::core::i64::MIN, ::core::i64::MAX];
+        let sizes = [1usize, 1, 1, 1, 2, 1, 2, 2, 5, 10, 10];
+        let mut buf = [0u8; 10];
+        for (&value, &size) in values.iter().zip(sizes.iter()) {
+            let sleb = Sleb128::new(value);
+            assert_eq!(sleb.size(), size);
+            buf.pwrite(sleb, 0).unwrap();
+            let num = buf.pread::<Sleb128>(0).unwrap();
+            assert_eq!(num, sleb);
+            assert_eq!(value, num.into());
+            for width in size..11 {
+                buf.pwrite(Sleb128::padded(value, width).unwrap(), 0).unwrap();
+                let num = buf.pread::<Sleb128>(0).unwrap();
+                assert_eq!(num.size(), width);
+                assert_eq!(value, num.into());
+            }
+        }
+        assert!(Sleb128::padded(64, 1).is_err());
+    }
+
+    #[test]
+    fn leb128_write() {
+        let mut buf = [0u8; 8];
+        let offset = &mut 0;
+        assert_eq!(Uleb128::write(&mut buf, offset, 130).unwrap(), 2);
+        assert_eq!(Sleb128::write(&mut buf, offset, -129).unwrap(), 2);
+        assert_eq!(*offset, 4);
+        let offset = &mut 0;
+        assert_eq!(Uleb128::read(&buf, offset).unwrap(), 130);
+        assert_eq!(Sleb128::read(&buf, offset).unwrap(), -129);
+        assert_eq!(*offset, 4);
+    }
 }
@@ -1,6 +1,7 @@
 use std::fmt::Debug;
+use std::result;
 use std::io::{Result, Read, Write};
-use ctx::{FromCtx, IntoCtx, SizeWith};
+use ctx::{self, FromCtx, IntoCtx, SizeWith, TryFromStream, TryIntoStream};
 use error::{self};
 
 /// An extension trait to `std::io::Read` streams; this only deserializes simple types, like `u8`, `i32`, `f32`, `usize`, etc.
@@ -73,7 +74,7 @@ pub trait Lread<Ctx = super::Endian, E = error::Error> : Read
     }
 
     /// Reads the type `N` from `Self`, with the parsing context `ctx`.
-    /// **NB**: this will panic if the type you're reading has a size greater than 256. Plans are to have this allocate in larger cases.
+    /// **NB**: this allocates if the type you're reading has a size greater than 256.
     ///
     /// For the primitive numeric types, this will be at the host machine's endianness.
     ///
@@ -94,17 +95,52 @@ pub trait Lread<Ctx = super::Endian, E = error::Error> : Read
     /// ```
     #[inline]
     fn lread_with<N: FromCtx<Ctx> + SizeWith<Ctx, Units = usize>>(&mut self, ctx: Ctx) -> Result<N> {
-        let mut scratch = [0u8; 256];
-        let size = N::size_with(&ctx);
-        let mut buf = &mut scratch[0..size];
-        self.read_exact(&mut buf)?;
-        Ok(N::from_ctx(buf, ctx))
+        ctx::with_scratch(N::size_with(&ctx), |buf| {
+            self.read_exact(buf)?;
+            Ok(N::from_ctx(buf, ctx))
+        })
+    }
+
+    /// Tries to read the type `N` from `Self`, with a default parsing context.
+    ///
+    /// See [lread_try_with](#method.lread_try_with).
+    #[inline]
+    fn lread_try<N: TryFromStream<Ctx, Error = E>>(&mut self) -> result::Result<N, E> {
+        let ctx = Ctx::default();
+        self.lread_try_with(ctx)
+    }
+
+    /// Tries to read the type `N` from `Self`, with the parsing context `ctx`, consuming exactly as many bytes as `N` occupies.
+    ///
+    /// Unlike `lread_with`, `N` only needs to implement [`TryFromStream`](ctx/trait.TryFromStream.html), so variable length types like `Uleb128`, or
+    /// an owned `String` or `Vec<u8>` with a `StrCtx`, can be read, as well as any `TryFromCtx + SizeWith` type, regardless of its size.
+    /// Short reads are reported as `Error::IO`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::{ctx, Lread, Uleb128, LE};
+    /// use std::io::Cursor;
+    /// let bytes = [0xe5, 0x8e, 0x26, b'h', b'i', 0x0, 0xef, 0xbe, 0x2, b'n', b'o'];
+    /// let mut bytes = Cursor::new(&bytes[..]);
+    /// let uleb = bytes.lread_try::<Uleb128>().unwrap();
+    /// assert_eq!(u64::from(uleb), 624485);
+    /// let hi = bytes.lread_try_with::<String>(ctx::NULL).unwrap();
+    /// assert_eq!(hi, "hi");
+    /// let beef = bytes.lread_try_with::<u16>(LE).unwrap();
+    /// assert_eq!(beef, 0xbeef);
+    /// let no = bytes.lread_try_with::<Vec<u8>>(ctx::StrCtx::Prefixed(ctx::LengthPrefix::U8, LE)).unwrap();
+    /// assert_eq!(no, b"no");
+    /// assert!(bytes.lread_try::<Uleb128>().is_err());
+    /// ```
+    #[inline]
+    fn lread_try_with<N: TryFromStream<Ctx, Error = E>>(&mut self, ctx: Ctx) -> result::Result<N, E> {
+        N::try_from_stream(self, ctx)
     }
 }
 
 /// Types that implement `Read` get methods defined in `Lread`
 /// for free.
-impl<R: Read + ?Sized> Lread for R {}
+impl<Ctx: Copy + Default + Debug, R: Read + ?Sized> Lread<Ctx> for R {}
 
 /// An extension trait to `std::io::Write` streams; this only serializes simple types, like `u8`, `i32`, `f32`, `usize`, etc.
 ///
@@ -114,8 +150,8 @@ pub trait Lwrite<Ctx = super::Endian, E = error::Error>: Write
           Ctx: Copy + Default + Debug,
           E: Debug,
 {
-    /// Writes the type `N` into `Self`, with the parsing context `ctx`.
-    /// **NB**: this will panic if the type you're writing has a size greater than 256. Plans are to have this allocate in larger cases.
+    /// Writes the type `N` into `Self`, with a default parsing context.
+    /// **NB**: this allocates if the type you're writing has a size greater than 256.
     ///
     /// For the primitive numeric types, this will be at the host machine's endianness.
     ///
@@ -136,7 +172,7 @@ pub trait Lwrite<Ctx = super::Endian, E = error::Error>: Write
     }
 
     /// Writes the type `N` into `Self`, with the parsing context `ctx`.
-    /// **NB**: this will panic if the type you're writing has a size greater than 256. Plans are to have this allocate in larger cases.
+    /// **NB**: this allocates if the type you're writing has a size greater than 256.
     ///
     /// For the primitive numeric types, this will be at the host machine's endianness.
     ///
@@ -153,15 +189,42 @@ pub trait Lwrite<Ctx = super::Endian, E = error::Error>: Write
     /// ```
     #[inline]
     fn lwrite_with<N: SizeWith<Ctx, Units = usize> + IntoCtx<Ctx>>(&mut self, n: N, ctx: Ctx) -> Result<()> {
-        let mut buf = [0u8; 256];
-        let size = N::size_with(&ctx);
-        let mut buf = &mut buf[0..size];
-        n.into_ctx(buf, ctx);
-        self.write_all(buf)?;
-        Ok(())
+        ctx::with_scratch(N::size_with(&ctx), |buf| {
+            n.into_ctx(buf, ctx);
+            self.write_all(buf)
+        })
+    }
+
+    /// Tries to write the type `N` into `Self`, with a default parsing context.
+    ///
+    /// See [lwrite_try_with](#method.lwrite_try_with).
+    #[inline]
+    fn lwrite_try<N: TryIntoStream<Ctx, Error = E>>(&mut self, n: N) -> result::Result<(), E> {
+        let ctx = Ctx::default();
+        self.lwrite_try_with(n, ctx)
+    }
+
+    /// Tries to write the type `N` into `Self`, with the parsing context `ctx`.
+    ///
+    /// `N` only needs to implement [`TryIntoStream`](ctx/trait.TryIntoStream.html), so variable length types like `Uleb128`, or
+    /// a `&str` or `&[u8]` with a `StrCtx`, can be written, as well as any `TryIntoCtx + SizeWith` type, regardless of its size.
+    ///
+    /// # Example
+    /// ```rust
+    /// use scroll::{ctx, Lwrite, Uleb128, BE};
+    /// use std::io::Cursor;
+    /// let mut cursor = Cursor::new(Vec::new());
+    /// cursor.lwrite_try(Uleb128::new(624485)).unwrap();
+    /// cursor.lwrite_try_with("hi", ctx::NULL).unwrap();
+    /// cursor.lwrite_try_with(0xbeefu16, BE).unwrap();
+    /// assert_eq!(cursor.into_inner(), [0xe5, 0x8e, 0x26, b'h', b'i', 0x0, 0xbe, 0xef]);
+    /// ```
+    #[inline]
+    fn lwrite_try_with<N: TryIntoStream<Ctx, Error = E>>(&mut self, n: N, ctx: Ctx) -> result::Result<(), E> {
+        n.try_into_stream(self, ctx)
     }
 }
 
 /// Types that implement `Write` get methods defined in `Lwrite`
 /// for free.
-impl<W: Write + ?Sized> Lwrite for W {}
+impl<Ctx: Copy + Default + Debug, W: Write + ?Sized> Lwrite<Ctx> for W {}
@@ -12,7 +12,7 @@
 //!
 //! ```
 //!
-//! Scroll is a library for efficiently and easily reading/writing types from byte arrays. All the builtin types are supported, e.g., `u32`, `i8`, etc., where the type is specified as a type parameter, or type inferred when possible. In addition, it supports zero-copy reading of string slices, or any other kind of slice.  The library can be used in a no_std context as well; the [Error](enum.Error.html) type only has the `IO` and `String` variants if the default features are used, and is `no_std` safe when compiled without default features.
+//! Scroll is a library for efficiently and easily reading/writing types from byte arrays. All the builtin types are supported, e.g., `u32`, `i8`, etc., where the type is specified as a type parameter, or type inferred when possible. In addition, it supports zero-copy reading of string slices, or any other kind of slice.  The library can be used in a no_std context as well; the [Error](enum.Error.html) type only has the `IO` and `String` variants if the default features are used, and is `no_std` safe when compiled without default features. With an allocator but no `std`, the `alloc` feature enables the `String` variant, `Buffer`, `VecWriter`, and reading owned `String`s and `Vec`s.
 //!
 //! There are 3 traits for reading that you can import:
 //!
@@ -108,6 +108,9 @@
 //! assert_eq!(cursor.into_inner(), [0x68, 0x65, 0x6c, 0x6c, 0x6f, 0xde, 0xad, 0xbe, 0xef, 0x0]);
 //! ```
 //!
+//! Files too large to read into memory can be accessed at `u64` offsets with [PreadAt](trait.PreadAt.html) and [PwriteAt](trait.PwriteAt.html), which work on a `File` directly,
+//! or on any `Read + Seek` stream wrapped in a [SeekAt](struct.SeekAt.html); values are returned owned, since nothing can be borrowed from a file.
+//!
 //! # Advanced Uses
 //!
 //! Scroll is designed to be highly configurable - it allows you to implement various context (`Ctx`) sensitive traits, which then grants the implementor _automatic_ uses of the `Pread`/`Gread` and/or `Pwrite`/`Gwrite` traits.
@@ -155,6 +158,10 @@
 #[cfg(feature = "std")]
 extern crate core;
 
+#[cfg(feature = "alloc")]
+#[macro_use]
+extern crate alloc;
+
 pub mod ctx;
 mod pread;
 mod pwrite;
@@ -162,10 +169,22 @@ mod greater;
 mod error;
 mod endian;
 mod leb128;
-#[cfg(feature = "std")]
+mod bits;
+mod align;
+mod pod;
+mod typed_endian;
+mod strtab;
+mod wide;
+#[cfg(feature = "alloc")]
+mod backpatch;
+#[cfg(feature = "alloc")]
 mod buffer;
+#[cfg(feature = "alloc")]
+mod writer;
 #[cfg(feature = "std")]
 mod lesser;
+#[cfg(feature = "std")]
+mod positional;
 
 pub use endian::*;
 pub use pread::*;
@@ -173,10 +192,22 @@ pub use pwrite::*;
 pub use greater::*;
 pub use error::*;
 pub use leb128::*;
-#[cfg(feature = "std")]
+pub use bits::*;
+pub use align::*;
+pub use pod::*;
+pub use typed_endian::*;
+pub use strtab::*;
+pub use wide::*;
+#[cfg(feature = "alloc")]
+pub use backpatch::*;
+#[cfg(feature = "alloc")]
 pub use buffer::*;
+#[cfg(feature = "alloc")]
+pub use writer::*;
 #[cfg(feature = "std")]
 pub use lesser::*;
+#[cfg(feature = "std")]
+pub use positional::*;
 
 #[cfg(test)]
 mod tests {
@@ -208,7 +239,7 @@ mod tests {
             #[test]
             fn $write() {
                 use super::{Pwrite, Pread, BE};
-                let bytes: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 0];
+                let bytes: [u8; 16] = [0; 16];
                 let mut b = Buffer::new(&bytes[..]);
                 b.pwrite_with::<$read>($deadbeef, 0, LE).unwrap();
                 assert_eq!(b.pread_with::<$read>(0, LE).unwrap(), $deadbeef);
@@ -224,6 +255,8 @@ mod tests {
     pwrite_test!(p_i32, i32, 0x7eefbeef);
     pwrite_test!(p_u64, u64, 0xbeefbeef7eef7eef);
     pwrite_test!(p_i64, i64, 0x7eefbeef7eef7eef);
+    pwrite_test!(p_u128, u128, 0xbeefbeef7eef7eefdeaddeadfeedface);
+    pwrite_test!(p_i128, i128, -0x7eefbeef7eef7eefdeaddeadfeedface);
 
     #[test]
     fn pread_with_be() {
@@ -306,7 +339,11 @@ mod tests {
         use super::ctx::{self, SPACE};
         let astring: &str = "lol hello_world lal\0ala imabytes";
         let mut buffer = Buffer::with(0, astring.len());
+        // no room for the null terminator
+        assert!(buffer.pwrite(astring, 0).is_err());
+        let mut buffer = Buffer::with(0, astring.len() + 1);
         buffer.pwrite(astring, 0).unwrap();
+        assert_eq!(buffer[astring.len()], 0);
         {
             let hello_world = buffer.pread_with::<&str>(4, SPACE).unwrap();
             assert_eq!(hello_world, "hello_world");
@@ -321,6 +358,62 @@ mod tests {
         assert_eq!(bytes, "bytes");
     }
 
+    #[test]
+    fn pread_str_ctx() {
+        use super::{Pread, BE, LE};
+        use super::ctx::{StrCtx, LengthPrefix};
+        let bytes: &[u8] = b"__TEXT\0\0\0\0\0\0\0\0\0\0__DATA_CONST____";
+        assert_eq!(bytes.pread_with::<&str>(0, StrCtx::Padded(16, 0)).unwrap(), "__TEXT");
+        assert_eq!(bytes.pread_with::<&str>(16, StrCtx::Padded(16, 0)).unwrap(), "__DATA_CONST____");
+        assert!(bytes.pread_with::<&str>(17, StrCtx::Padded(16, 0)).is_err());
+        assert_eq!(bytes.pread_with::<&str>(0, StrCtx::Length(2)).unwrap(), "__");
+        assert_eq!(bytes.pread_with::<&str>(0, StrCtx::Length(0)).unwrap(), "");
+        assert!(bytes.pread_with::<&str>(16, StrCtx::Length(17)).is_err());
+        let pascal: &[u8] = b"\x05hello";
+        assert_eq!(pascal.pread_with::<&str>(0, StrCtx::Prefixed(LengthPrefix::U8, LE)).unwrap(), "hello");
+        let pascal: &[u8] = b"\x06hello";
+        assert!(pascal.pread_with::<&str>(0, StrCtx::Prefixed(LengthPrefix::U8, LE)).is_err());
+        let u16be: &[u8] = b"\x00\x02hi";
+        assert_eq!(u16be.pread_with::<&str>(0, StrCtx::Prefixed(LengthPrefix::U16, BE)).unwrap(), "hi");
+        let u32le: &[u8] = b"\x02\x00\x00\x00hi";
+        assert_eq!(u32le.pread_with::<&str>(0, StrCtx::Prefixed(LengthPrefix::U32, LE)).unwrap(), "hi");
+        let uleb: &[u8] = b"\x82\x00hi";
+        assert_eq!(uleb.pread_with::<&str>(0, StrCtx::Prefixed(LengthPrefix::Uleb128, LE)).unwrap(), "hi");
+        let invalid: &[u8] = b"\x02\xff\xfe";
+        assert!(invalid.pread_with::<&str>(0, StrCtx::Prefixed(LengthPrefix::U8, LE)).is_err());
+    }
+
+    #[test]
+    fn pwrite_str_ctx() {
+        use super::{Pread, Pwrite, BE, LE};
+        use super::ctx::{self, StrCtx, LengthPrefix};
+        let mut bytes = [0xffu8; 16];
+        bytes.pwrite_with("__TEXT", 0, StrCtx::Padded(16, 0)).unwrap();
+        assert_eq!(&bytes, b"__TEXT\0\0\0\0\0\0\0\0\0\0");
+        assert!(bytes.pwrite_with("__TEXT", 1, StrCtx::Padded(16, 0)).is_err());
+        assert!(bytes.pwrite_with("01234567890123456", 0, StrCtx::Padded(16, 0)).is_err());
+        bytes.pwrite_with("hi", 0, ctx::SPACE).unwrap();
+        assert_eq!(&bytes[..3], b"hi ");
+        bytes.pwrite_with("hey", 0, StrCtx::Length(3)).unwrap();
+        assert_eq!(&bytes[..4], b"heyE");
+        assert!(bytes.pwrite_with("hey", 0, StrCtx::Length(2)).is_err());
+        let prefixes = [LengthPrefix::U8, LengthPrefix::U16, LengthPrefix::U32, LengthPrefix::Uleb128];
+        for prefix in prefixes.iter() {
+            for le in [LE, BE].iter() {
+                let ctx = StrCtx::Prefixed(*prefix, *le);
+                let mut bytes = [0u8; 16];
+                bytes.pwrite_with("hello", 3, ctx).unwrap();
+                assert_eq!(bytes.pread_with::<&str>(3, ctx).unwrap(), "hello");
+                assert!(bytes.pwrite_with("hello", 12, ctx).is_err());
+            }
+        }
+        let long = ::std::iter::repeat('a').take(256).collect::<String>();
+        let mut bytes = [0u8; 512];
+        assert!(bytes.pwrite_with(long.as_str(), 0, StrCtx::Prefixed(LengthPrefix::U8, LE)).is_err());
+        bytes.pwrite_with(long.as_str(), 0, StrCtx::Prefixed(LengthPrefix::Uleb128, LE)).unwrap();
+        assert_eq!(&bytes[..3], b"\x80\x02a");
+    }
+
     use std::error;
     use std::fmt::{self, Display};
 
@@ -460,6 +553,151 @@ mod tests {
         assert_eq!(bytes_to, bytes_from);
     }
 
+    #[test]
+    fn cread_cwrite_128() {
+        use super::{Cread, Cwrite, LE, BE};
+        let mut bytes = [0u8; 16];
+        bytes.cwrite_with(0x0102030405060708090a0b0c0d0e0f10u128, 0, BE);
+        assert_eq!(bytes[0], 0x01);
+        assert_eq!(bytes[15], 0x10);
+        assert_eq!(bytes.cread_with::<u128>(0, BE), 0x0102030405060708090a0b0c0d0e0f10);
+        bytes.cwrite_with(-2i128, 0, LE);
+        assert_eq!(bytes[0], 0xfe);
+        assert_eq!(bytes[15], 0xff);
+        assert_eq!(bytes.cread_with::<i128>(0, LE), -2);
+    }
+
+    macro_rules! lread_lwrite_test {
+        ($name:ident, $val:expr, $typ:ty) => {
+            #[test]
+            fn $name() {
+                use std::io::Cursor;
+                use super::{Lread, Lwrite, LE, BE};
+                let mut cursor = Cursor::new(Vec::new());
+                cursor.lwrite_with::<$typ>($val, LE).unwrap();
+                cursor.lwrite_with::<$typ>($val, BE).unwrap();
+                assert_eq!(cursor.get_ref().len(), 2 * ::std::mem::size_of::<$typ>());
+                cursor.set_position(0);
+                assert_eq!(cursor.lread_with::<$typ>(LE).unwrap(), $val);
+                assert_eq!(cursor.lread_with::<$typ>(BE).unwrap(), $val);
+                assert!(cursor.lread::<$typ>().is_err());
+            }
+        }
+    }
+
+    lread_lwrite_test!(lread_lwrite_u128, 0xdeadbeef_00112233_44556677_8899aabbu128, u128);
+    lread_lwrite_test!(lread_lwrite_i128, -0x0011_2233_4455_6677_8899_aabb_ccdd_eeffi128, i128);
+
+    #[test]
+    fn lread_lwrite_try_leb128() {
+        use std::io::Cursor;
+        use super::{Lread, Lwrite, Uleb128, Sleb128};
+        let mut cursor = Cursor::new(Vec::new());
+        cursor.lwrite_try(Uleb128::new(::std::u64::MAX)).unwrap();
+        cursor.lwrite_try(Sleb128::new(-129)).unwrap();
+        cursor.lwrite_try(Uleb128::padded(1, 5).unwrap()).unwrap();
+        assert_eq!(cursor.get_ref().len(), 17);
+        cursor.set_position(0);
+        assert_eq!(u64::from(cursor.lread_try::<Uleb128>().unwrap()), ::std::u64::MAX);
+        assert_eq!(i64::from(cursor.lread_try::<Sleb128>().unwrap()), -129);
+        let padded = cursor.lread_try::<Uleb128>().unwrap();
+        assert_eq!(padded.size(), 5);
+        assert_eq!(u64::from(padded), 1);
+        match cursor.lread_try::<Uleb128>() {
+            Err(super::Error::IO(_)) => (),
+            res => panic!("expected an IO error, got {:?}", res),
+        }
+        // too many continuation bytes
+        let mut cursor = Cursor::new([0x80u8; 16]);
+        match cursor.lread_try::<Uleb128>() {
+            Err(super::Error::BadInput { .. }) => (),
+            res => panic!("expected a BadInput error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn lread_lwrite_try_str() {
+        use std::io::Cursor;
+        use super::{Lread, Lwrite, LE, BE};
+        use super::ctx::{self, StrCtx, LengthPrefix};
+        let ctxs = [ctx::NULL, ctx::SPACE, StrCtx::Length(5), StrCtx::Padded(8, 0),
+                    StrCtx::Prefixed(LengthPrefix::U8, LE), StrCtx::Prefixed(LengthPrefix::U16, BE),
+                    StrCtx::Prefixed(LengthPrefix::U32, LE), StrCtx::Prefixed(LengthPrefix::Uleb128, LE)];
+        for ctx in ctxs.iter() {
+            let mut cursor = Cursor::new(Vec::new());
+            cursor.lwrite_try_with("hello", *ctx).unwrap();
+            cursor.lwrite_try_with(&b"world"[..], *ctx).unwrap();
+            let len = cursor.get_ref().len();
+            cursor.set_position(0);
+            assert_eq!(cursor.lread_try_with::<String>(*ctx).unwrap(), "hello");
+            assert_eq!(cursor.lread_try_with::<Vec<u8>>(*ctx).unwrap(), b"world");
+            assert_eq!(cursor.position() as usize, len);
+            // short read
+            let mut cursor = Cursor::new(&cursor.get_ref()[..len - 1]);
+            cursor.lread_try_with::<String>(*ctx).unwrap();
+            match cursor.lread_try_with::<Vec<u8>>(*ctx) {
+                Err(super::Error::IO(_)) => (),
+                res => panic!("expected an IO error, got {:?}", res),
+            }
+        }
+        let mut cursor = Cursor::new(b"\xff\xfe\0");
+        assert!(cursor.lread_try_with::<String>(ctx::NULL).is_err());
+        let mut cursor = Cursor::new(Vec::new());
+        assert!(cursor.lwrite_try_with("hello", StrCtx::Padded(4, 0)).is_err());
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Big {
+        bytes: Vec<u8>,
+        last: u32,
+    }
+
+    impl<'a> super::ctx::TryFromCtx<'a, (usize, super::Endian)> for Big {
+        type Error = super::Error;
+        fn try_from_ctx(src: &'a [u8], (offset, le): (usize, super::Endian)) -> super::Result<Self> {
+            use super::Pread;
+            let bytes = src.pread_slice::<[u8]>(offset, 300)?.to_vec();
+            let last = src.pread_with(offset + 300, le)?;
+            Ok(Big { bytes, last })
+        }
+    }
+
+    impl<'a> super::ctx::TryIntoCtx<(usize, super::Endian)> for &'a Big {
+        type Error = super::Error;
+        fn try_into_ctx(self, dst: &mut [u8], (offset, le): (usize, super::Endian)) -> super::Result<()> {
+            use super::Pwrite;
+            dst.pwrite(self.bytes.as_slice(), offset)?;
+            dst.pwrite_with(self.last, offset + 300, le)
+        }
+    }
+
+    impl super::ctx::SizeWith for Big {
+        type Units = usize;
+        fn size_with(_: &super::Endian) -> usize {
+            304
+        }
+    }
+
+    impl<'a> super::ctx::SizeWith for &'a Big {
+        type Units = usize;
+        fn size_with(ctx: &super::Endian) -> usize {
+            Big::size_with(ctx)
+        }
+    }
+
+    #[test]
+    fn lread_lwrite_try_big() {
+        use std::io::Cursor;
+        use super::{Lread, Lwrite, BE};
+        let big = Big { bytes: (0..300).map(|i| i as u8).collect(), last: 0xdeadbeef };
+        let mut cursor = Cursor::new(Vec::new());
+        cursor.lwrite_try_with(&big, BE).unwrap();
+        assert_eq!(cursor.get_ref().len(), 304);
+        cursor.set_position(0);
+        assert_eq!(cursor.lread_try_with::<Big>(BE).unwrap(), big);
+        assert!(cursor.lread_try_with::<Big>(BE).is_err());
+    }
+
     //////////////////////////////////////////////////////////////
     // end pread_with
     //////////////////////////////////////////////////////////////
@@ -511,7 +749,7 @@ mod tests {
             #[test]
             fn $read() {
                 use super::{LE, BE, Gread, Gwrite};
-                let mut buffer = Buffer::with(0, 16);
+                let mut buffer = Buffer::with(0, 32);
                 let mut offset = &mut 0;
                 buffer.gwrite_with($val.clone(), offset, LE).unwrap();
                 let mut o2 = &mut 0;
@@ -543,6 +781,11 @@ mod tests {
     g_read_write_test!(gread_gwrite_i32_2, -1213213232, i32);
     g_read_write_test!(gread_gwrite_i32_3, -3000i32, i32);
 
+    g_read_write_test!(gread_gwrite_u128_1, 0u128, u128);
+    g_read_write_test!(gread_gwrite_u128_2, 0xdeadbeef_00112233_44556677_8899aabbu128, u128);
+    g_read_write_test!(gread_gwrite_i128_1, -1i128, i128);
+    g_read_write_test!(gread_gwrite_i128_2, -0x0011_2233_4455_6677_8899_aabb_ccdd_eeffi128, i128);
+
     // useful for ferreting out problems with impls
     #[test]
     fn gread_with_iter_bytes() {
@@ -637,6 +880,424 @@ mod tests {
         }
     }
 
+    #[test]
+    fn array_ctx() {
+        use super::{Pread, Pwrite, Gread, Gwrite, Cread, Cwrite, LE, BE};
+        use super::ctx::SizeWith;
+        let bytes: [u8; 9] = [0xff, 0, 1, 0, 2, 0, 3, 0, 4];
+        assert_eq!(<[u16; 4]>::size_with(&LE), 8);
+        assert_eq!(bytes.pread_with::<[u16; 4]>(1, BE).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(bytes.pread_with::<[u16; 4]>(1, LE).unwrap(), [0x100, 0x200, 0x300, 0x400]);
+        assert!(bytes.pread_with::<[u16; 4]>(2, LE).is_err());
+        assert_eq!(bytes.cread_with::<[u16; 4]>(1, BE), [1, 2, 3, 4]);
+        assert_eq!(bytes.pread::<[u8; 0]>(9).unwrap(), []);
+        let offset = &mut 1;
+        let quad: [u16; 4] = bytes.gread_with(offset, BE).unwrap();
+        assert_eq!(quad, [1, 2, 3, 4]);
+        assert_eq!(*offset, 9);
+        let nested: [[u8; 2]; 2] = bytes.pread(1).unwrap();
+        assert_eq!(nested, [[0, 1], [0, 2]]);
+
+        let mut buffer = [0u8; 9];
+        let offset = &mut 1;
+        buffer.gwrite_with([1u16, 2, 3, 4], offset, BE).unwrap();
+        assert_eq!(*offset, 9);
+        assert_eq!(buffer[1..], bytes[1..]);
+        assert!(buffer.pwrite_with([1u16, 2, 3, 4], 2, BE).is_err());
+        let mut buffer = [0u8; 8];
+        buffer.cwrite_with([0x0102u16, 0x0304, 0x0506, 0x0708], 0, LE);
+        assert_eq!(buffer, [2, 1, 4, 3, 6, 5, 8, 7]);
+    }
+
+    #[test]
+    fn tuple_ctx() {
+        use super::{Pread, Pwrite, Gread, Gwrite, Cread, Cwrite, LE, BE};
+        use super::ctx::SizeWith;
+        let bytes: [u8; 7] = [1, 0, 2, 0, 0, 0, 3];
+        assert_eq!(<(u16, u32, u8)>::size_with(&LE), 7);
+        assert_eq!(bytes.pread_with::<(u16, u32, u8)>(0, LE).unwrap(), (1, 2, 3));
+        assert_eq!(bytes.pread_with::<(u8,)>(6, LE).unwrap(), (3,));
+        assert!(bytes.pread_with::<(u16, u32, u16)>(0, LE).is_err());
+        assert_eq!(bytes.cread_with::<(u8, u16)>(0, BE), (1, 2));
+        let offset = &mut 0;
+        let (a, b): (u16, u16) = bytes.gread_with(offset, BE).unwrap();
+        assert_eq!((a, b), (0x100, 0x200));
+        assert_eq!(*offset, 4);
+        let pairs: [(u8, u8); 2] = bytes.pread(0).unwrap();
+        assert_eq!(pairs, [(1, 0), (2, 0)]);
+
+        let mut buffer = [0u8; 7];
+        let offset = &mut 0;
+        buffer.gwrite_with((1u16, 2u32, 3u8), offset, LE).unwrap();
+        assert_eq!(*offset, 7);
+        assert_eq!(buffer, bytes);
+        assert!(buffer.pwrite_with((1u16, 2u32, 3u8), 1, LE).is_err());
+        let mut buffer = [0u8; 3];
+        buffer.cwrite_with((1u8, 2u16), 0, BE);
+        assert_eq!(buffer, [1, 0, 2]);
+    }
+
+    #[test]
+    fn lread_lwrite_array_tuple() {
+        use std::io::Cursor;
+        use super::{Lread, Lwrite, BE};
+        let mut cursor = Cursor::new(Vec::new());
+        cursor.lwrite_with([1u32, 2, 3, 4], BE).unwrap();
+        cursor.lwrite_with((5u16, -6i64), BE).unwrap();
+        cursor.lwrite_try_with([7u8; 300], BE).unwrap();
+        cursor.set_position(0);
+        assert_eq!(cursor.lread_with::<[u32; 4]>(BE).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(cursor.lread_try_with::<(u16, i64)>(BE).unwrap(), (5, -6));
+        assert_eq!(cursor.lread_with::<[u8; 300]>(BE).unwrap()[..], [7u8; 300][..]);
+    }
+
+    #[test]
+    fn gread_vec_fixed() {
+        use super::{Gread, Pread, BE};
+        let bytes = [0u8, 1, 0, 2, 0, 3, 0xff];
+        let offset = &mut 0;
+        let vec: Vec<u16> = bytes.gread_vec_with(offset, 3, BE).unwrap();
+        assert_eq!(vec, [1, 2, 3]);
+        assert_eq!(*offset, 6);
+        // checked up front, and the offset isn't moved
+        let offset = &mut 2;
+        assert!(bytes.gread_vec_with::<u16>(offset, 3, BE).is_err());
+        assert_eq!(*offset, 2);
+        assert!(bytes.pread_vec::<u64>(0, usize::MAX).is_err());
+        let empty: Vec<u32> = bytes.pread_vec(7, 0).unwrap();
+        assert!(empty.is_empty());
+        let pairs: Vec<(u8, u8)> = bytes.pread_vec_with(1, 3, BE).unwrap();
+        assert_eq!(pairs, [(1, 0), (2, 0), (3, 0xff)]);
+    }
+
+    #[test]
+    fn gread_vec_variable() {
+        use super::{ctx, Gread, Pread, Uleb128, Sleb128};
+        use super::ctx::{StrCtx, LengthPrefix};
+        let bytes = b"one\0two\0three";
+        let offset = &mut 0;
+        let strings: Vec<&str> = bytes.gread_vec_with(offset, 3, ctx::NULL).unwrap();
+        assert_eq!(strings, ["one", "two", "three"]);
+        assert_eq!(*offset, bytes.len());
+        let bytes = b"\x02hi\x03you\x00";
+        let strings: Vec<&str> = bytes.pread_vec_with(0, 3, StrCtx::Prefixed(LengthPrefix::U8, super::LE)).unwrap();
+        assert_eq!(strings, ["hi", "you", ""]);
+        let bytes = [0x80, 0x01, 0x7f, 0xe5, 0x8e, 0x26];
+        let offset = &mut 0;
+        let ulebs: Vec<Uleb128> = bytes.gread_vec(offset, 3).unwrap();
+        assert_eq!(ulebs, [Uleb128::new(128), Uleb128::new(127), Uleb128::new(624485)]);
+        assert_eq!(*offset, 6);
+        let slebs: Vec<Sleb128> = bytes.pread_vec(2, 1).unwrap();
+        assert_eq!(slebs, [Sleb128::new(-1)]);
+        // runs off the end partway through; the offset isn't moved
+        let offset = &mut 0;
+        assert!(bytes.gread_vec::<Uleb128>(offset, 4).is_err());
+        assert_eq!(*offset, 0);
+        // an untrusted count of empty items is an error, rather than a spin
+        assert!(bytes.pread_vec_with::<&str>(0, usize::MAX, StrCtx::Length(0)).is_err());
+        assert!(bytes.pread_vec_with::<&str>(0, 1, StrCtx::Length(0)).is_err());
+    }
+
+    #[test]
+    fn gread_inout_ctx() {
+        use super::{Gread, BE};
+        let bytes = [0u8, 1, 0, 2];
+        let mut out = [0u16; 2];
+        bytes.gread_inout_with(&mut 0, &mut out, BE).unwrap();
+        assert_eq!(out, [1, 2]);
+        let offset = &mut 0;
+        bytes.gread_inout(offset, &mut out).unwrap();
+        assert_eq!(out, [u16::from_ne_bytes([0, 1]), u16::from_ne_bytes([0, 2])]);
+        assert_eq!(*offset, 4);
+    }
+
+    #[test]
+    fn table_fixed() {
+        use super::{Table, LE};
+        let bytes = [1u8, 0, 2, 0, 3, 0, 4, 0, 5];
+        let mut table: Table<u16> = Table::new(&bytes, 0, 4, LE);
+        assert_eq!(table.len(), 4);
+        assert!(table.get(4).is_none());
+        assert_eq!(table.get(3).unwrap().unwrap(), 4);
+        assert_eq!(table.next().unwrap().unwrap(), 1);
+        assert_eq!(table.next_back().unwrap().unwrap(), 4);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.get(0).unwrap().unwrap(), 2);
+        assert_eq!(table.next_back().unwrap().unwrap(), 3);
+        assert_eq!(table.next().unwrap().unwrap(), 2);
+        assert!(table.next().is_none());
+        assert!(table.next_back().is_none());
+        assert!(table.is_empty());
+        assert_eq!(table.offset(), 4);
+        // the trailing odd byte is a partial element, which is an error from either end
+        let table: Table<u16> = Table::with_end(&bytes, 0, bytes.len(), LE);
+        assert_eq!(table.len(), 5);
+        assert!(table.clone().next_back().unwrap().is_err());
+        let all: Vec<_> = table.collect();
+        assert_eq!(all.len(), 5);
+        assert!(all[..4].iter().all(|n| n.is_ok()));
+        assert!(all[4].is_err());
+        // elements can't read past the end offset, even if there are bytes after it
+        let mut table: Table<u32> = Table::with_end(&bytes, 2, 8, LE);
+        assert_eq!(table.next().unwrap().unwrap(), 0x0004_0003_u32 << 16 | 2);
+        assert!(table.next().unwrap().is_err());
+        assert!(table.next().is_none());
+    }
+
+    #[test]
+    fn table_variable() {
+        use super::{ctx, Table, Uleb128};
+        let bytes = b"\0libc.so\0_start\0main\0";
+        let strings = Table::<&str, _>::with_end(bytes, 1, bytes.len(), ctx::NULL);
+        assert_eq!(strings.map(Result::unwrap).collect::<Vec<_>>(), ["libc.so", "_start", "main"]);
+        let bytes = [0xe5, 0x8e, 0x26, 0x01, 0x80, 0x80];
+        let mut ulebs: Table<Uleb128> = Table::new(&bytes, 0, 3, ctx::CTX);
+        assert_eq!(ulebs.size_hint(), (1, Some(3)));
+        assert_eq!(ulebs.next().unwrap().unwrap(), Uleb128::new(624485));
+        assert_eq!(ulebs.next().unwrap().unwrap(), Uleb128::new(1));
+        assert_eq!(ulebs.offset(), 4);
+        // truncated
+        assert!(ulebs.next().unwrap().is_err());
+        assert!(ulebs.next().is_none());
+        assert_eq!(ulebs.offset(), 4);
+        assert_eq!(ulebs.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn pread_owned_strings() {
+        use super::{ctx, Pread, Pwrite, Gread, LE};
+        use super::ctx::{StrCtx, LengthPrefix};
+        let bytes = b"hello\0\x03\xffyo";
+        let hello: String = bytes.pread(0).unwrap();
+        assert_eq!(hello, "hello");
+        assert!(bytes.pread_with::<String>(6, StrCtx::Prefixed(LengthPrefix::U8, LE)).is_err());
+        let offset = &mut 0;
+        let strings: Vec<String> = bytes.gread_vec_with(offset, 1, ctx::NULL).unwrap();
+        assert_eq!(strings, ["hello"]);
+        assert_eq!(*offset, 6);
+        let mut out = [0u8; 6];
+        out.pwrite(&hello, 0).unwrap();
+        assert_eq!(&out, b"hello\0");
+    }
+
+    #[test]
+    fn context_error_path() {
+        use super::{Pread, Frame, FrameKind, ContextError, WithContext, Error, LE};
+        let bytes = [0u8; 2];
+        let res: Result<u32, ContextError> = bytes.pread_with::<u32>(1, LE)
+            .context(Frame::ty("Nlist", 1))
+            .context(Frame::field("n_strx", 1))
+            .context(Frame::ty("Ignored", 0))
+            .context(Frame::element("symbols", 7, 1))
+            .context(Frame::ty("SymtabCommand", 0));
+        let err = res.unwrap_err();
+        assert_eq!(err.path().frames().len(), 5);
+        assert_eq!(err.path().frames()[0], Frame::ty("Nlist", 1));
+        assert_eq!(err.path().frames()[3].kind, FrameKind::Element(7));
+        assert_eq!(err.to_string(), "SymtabCommand.symbols[7].n_strx @ 0x1: requested range [0x1..0x5) from object of len 0x2");
+        match err.into_inner() {
+            Error::BadRange { range, size } => { assert_eq!(range, 1..5); assert_eq!(size, 2) },
+            err => panic!("expected a BadRange, got {:?}", err),
+        }
+        // no frames
+        let err: ContextError = Error::BadOffset(16).into();
+        assert_eq!(err.to_string(), "bad offset 0x10");
+        let err = Err::<(), _>(Error::BadInput { range: 0..1, size: 1, msg: "bad magic" }).context(Frame::field("magic", 0)).unwrap_err();
+        assert_eq!(err.to_string(), "magic @ 0x0: bad magic - range [0x0..0x1), len 0x1");
+    }
+
+    #[test]
+    fn context_error_bounded() {
+        use super::{Frame, ContextError, WithContext, Error, MAX_FRAMES};
+        let mut res: Result<(), ContextError> = Err(Error::BadOffset(0).into());
+        for _ in 0..MAX_FRAMES + 2 {
+            res = res.context(Frame::field("next", 0));
+        }
+        let err = res.unwrap_err();
+        assert_eq!(err.path().frames().len(), MAX_FRAMES);
+        assert_eq!(err.path().dropped(), 2);
+        assert!(err.to_string().starts_with("...next.next"));
+    }
+
+    #[test]
+    fn offset_types() {
+        use super::{Pread, Pwrite, Gread, Gwrite, Error, BE};
+        let mut bytes = [0u8; 300];
+        bytes.pwrite_offset_with(0xdeadbeefu32, 260u64, BE).unwrap();
+        bytes.pwrite_offset_with(0xcafeu16, 4u32, BE).unwrap();
+        assert_eq!(bytes.pread_offset_with::<u32, _>(260u64, BE).unwrap(), 0xdeadbeef);
+        assert_eq!(bytes.pread_offset_with::<u16, _>(4u32, BE).unwrap(), 0xcafe);
+        let offset = &mut 260u64;
+        assert_eq!(bytes.gread_offset_with::<u16, _>(offset, BE).unwrap(), 0xdead);
+        assert_eq!(*offset, 262);
+        let offset = &mut 2u32;
+        bytes.gwrite_offset_with(0x1234u16, offset, BE).unwrap();
+        assert_eq!(*offset, 4);
+        assert_eq!(bytes.gread_offset::<u16, _>(offset).unwrap(), 0xcafe_u16.to_be());
+        assert_eq!(*offset, 6);
+        // the new offset doesn't fit, so the offset isn't touched
+        let offset = &mut 254u8;
+        match bytes.gread_offset_with::<u32, _>(offset, BE) {
+            Err(Error::BadOffsetType { offset: 258, ty: "u8" }) => (),
+            res => panic!("expected a BadOffsetType, got {:?}", res),
+        }
+        assert_eq!(*offset, 254);
+        assert!(bytes.gwrite_offset_with(1u32, offset, BE).is_err());
+        assert_eq!(*offset, 254);
+        // out of bounds is still a range error
+        assert!(bytes.pread_offset_with::<u32, _>(298u64, BE).is_err());
+        // and plain integer literals are still `usize` offsets
+        let offset = &mut 0;
+        assert_eq!(bytes.gread_with::<u16>(offset, BE).unwrap(), 0);
+        assert_eq!(*offset, 2);
+    }
+
+    #[test]
+    fn offset_type_impls() {
+        use super::{Pread, Pwrite, Gread, Gwrite, TryOffsetWith, Offset, Endian, Error, Uleb128, LE};
+        use super::ctx::{TryFromCtx, TryIntoCtx, TryRefFromCtx, SizeWith};
+        // a target indexed by `u32`s, which hands the primitive ctx impls a `usize`
+        struct Table32([u8; 8]);
+        type TryCtx = (usize, Endian);
+        type SliceCtx = (usize, usize, Endian);
+        impl Pread<Endian, Error, u32, TryCtx, SliceCtx> for Table32 {
+            fn pread_with<'a, N: TryFromCtx<'a, TryCtx, Error = Error>>(&'a self, offset: u32, ctx: Endian) -> Result<N, Error> {
+                N::try_from_ctx(&self.0, (offset.try_into_index()?, ctx))
+            }
+            fn pread_slice<'a, N: ?Sized + TryRefFromCtx<SliceCtx, Error = Error>>(&'a self, offset: u32, count: u32) -> Result<&'a N, Error> {
+                N::try_ref_from_ctx(&self.0, (offset.try_into_index()?, count.try_into_index()?, Endian::default()))
+            }
+        }
+        impl Pwrite<Endian, Error, u32, TryCtx, SliceCtx> for Table32 {
+            fn pwrite_with<N: TryIntoCtx<TryCtx, Error = Error>>(&mut self, n: N, offset: u32, ctx: Endian) -> Result<(), Error> {
+                n.try_into_ctx(&mut self.0, (offset.try_into_index()?, ctx))
+            }
+        }
+        impl TryOffsetWith<Endian, Error, u32> for Table32 {
+            fn try_offset<N: SizeWith<Endian, Units = u32>>(&self, offset: u32, ctx: &Endian) -> Result<u32, Error> {
+                let size = N::size_with(ctx);
+                super::error::check_range(offset.try_into_index()?, size.try_into_index()?, self.0.len())?;
+                Ok(size)
+            }
+        }
+        impl Gread<Endian, Error, u32, TryCtx, SliceCtx> for Table32 {}
+        impl Gwrite<Endian, Error, u32, TryCtx, SliceCtx> for Table32 {}
+        let mut table = Table32([0; 8]);
+        let offset = &mut 1u32;
+        table.gwrite_with(0xbeefu16, offset, LE).unwrap();
+        table.gwrite(Uleb128::new(300), offset).unwrap();
+        assert_eq!(*offset, 5);
+        assert_eq!(table.0, [0, 0xef, 0xbe, 0xac, 0x02, 0, 0, 0]);
+        let offset = &mut 1u32;
+        assert_eq!(table.gread_with::<u16>(offset, LE).unwrap(), 0xbeef);
+        assert_eq!(table.gread::<Uleb128>(offset).unwrap(), Uleb128::new(300));
+        assert_eq!(*offset, 5);
+        assert!(table.gread::<u32>(&mut 6).is_err());
+    }
+
+    #[test]
+    fn align_padding() {
+        use super::{Align, Gwrite, Gread, Error, LE};
+        let mut bytes = [0xffu8; 16];
+        let offset = &mut 0;
+        bytes.gwrite_with(1u8, offset, LE).unwrap();
+        bytes.gwrite_align(offset, 8, 0).unwrap();
+        bytes.gwrite_with(2u16, offset, LE).unwrap();
+        bytes.gwrite_align(offset, 4, 0xee).unwrap();
+        assert_eq!(*offset, 12);
+        assert_eq!(bytes[..12], [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0xee, 0xee]);
+        let offset = &mut 0;
+        assert_eq!(bytes.gread_with::<u8>(offset, LE).unwrap(), 1);
+        bytes.gread_align_zeroed(offset, 8).unwrap();
+        assert_eq!(bytes.gread_with::<u16>(offset, LE).unwrap(), 2);
+        match bytes.gread_align_zeroed(offset, 4) {
+            Err(Error::BadInput { range, size: 16, msg: "padding is not zero" }) => assert_eq!(range, 10..12),
+            res => panic!("expected a BadInput, got {:?}", res),
+        }
+        assert_eq!(*offset, 10);
+        bytes.gread_align(offset, 4).unwrap();
+        assert_eq!(*offset, 12);
+        // zero alignment and padding past the end are errors, and leave the offset alone
+        match bytes.gread_align(offset, 0) {
+            Err(Error::BadInput { msg: "alignment must be non-zero", .. }) => (),
+            res => panic!("expected a BadInput, got {:?}", res),
+        }
+        match bytes.gread_align(&mut 13, 32) {
+            Err(Error::BadRange { range, size: 16 }) => assert_eq!(range, 13..32),
+            res => panic!("expected a BadRange, got {:?}", res),
+        }
+        assert!(bytes.gwrite_align(&mut 13, 32, 0).is_err());
+        assert_eq!(bytes[13..], [0xff, 0xff, 0xff]);
+        bytes.pwrite_zeroes(12, 4).unwrap();
+        assert_eq!(bytes[12..], [0, 0, 0, 0]);
+        bytes.pwrite_fill(0xab, 14, 2).unwrap();
+        assert_eq!(bytes[12..], [0, 0, 0xab, 0xab]);
+        assert!(bytes.pwrite_fill(0, 15, 2).is_err());
+    }
+
+    #[test]
+    fn gwrite_variable_length() {
+        use super::{Gwrite, Pread, Uleb128, Sleb128, LE};
+        use super::ctx::{StrCtx, LengthPrefix};
+        let mut bytes = [0xffu8; 16];
+        let offset = &mut 0;
+        bytes.gwrite(Uleb128::new(1), offset).unwrap();
+        bytes.gwrite(Sleb128::new(-129), offset).unwrap();
+        bytes.gwrite_with("abc", offset, StrCtx::Prefixed(LengthPrefix::U8, LE)).unwrap();
+        bytes.gwrite_with("de", offset, StrCtx::Padded(4, b' ')).unwrap();
+        bytes.gwrite(&[1u8, 2][..], offset).unwrap();
+        assert_eq!(*offset, 13);
+        assert_eq!(bytes.pread::<Uleb128>(0).unwrap(), Uleb128::new(1));
+        assert_eq!(bytes[3..], [3, b'a', b'b', b'c', b'd', b'e', b' ', b' ', 1, 2, 0xff, 0xff, 0xff]);
+        // a failed write leaves the offset alone
+        assert!(bytes.gwrite_with("toolong", offset, StrCtx::Delimiter(0)).is_err());
+        assert_eq!(*offset, 13);
+        // and it works inside a generic parameter
+        fn _gwrite_api<W: super::Gwrite + ?Sized>(bytes: &mut W, offset: &mut usize) -> Result<(), super::Error> {
+            bytes.gwrite(Uleb128::new(300), offset)?;
+            bytes.gwrite_with(0xbeefu16, offset, LE)
+        }
+        *offset = 0;
+        _gwrite_api(&mut bytes[..], offset).unwrap();
+        assert_eq!(*offset, 4);
+        assert_eq!(bytes[..4], [0xac, 0x02, 0xef, 0xbe]);
+    }
+
+    #[test]
+    fn gread_variable_length() {
+        use super::{Gread, Pread, Uleb128, Error, LE};
+        use super::ctx::{self, TryReadCtx};
+
+        #[derive(Debug, PartialEq)]
+        struct Entry<'a> {
+            name: &'a str,
+            size: u64,
+            flags: u32,
+        }
+
+        impl<'a> TryReadCtx<'a, (usize, super::Endian)> for Entry<'a> {
+            type Error = Error;
+            fn try_read_ctx(src: &'a [u8], (offset, le): (usize, super::Endian)) -> Result<(Self, usize), Error> {
+                let mut o = offset;
+                let name = src.gread_with(&mut o, ctx::NULL)?;
+                let size: Uleb128 = src.gread(&mut o)?;
+                let flags = src.gread_with(&mut o, le)?;
+                Ok((Entry { name, size: size.into(), flags }, o - offset))
+            }
+        }
+
+        let bytes = [b'a', 0, 0x80, 0x01, 1, 0, 0, 0, b'b', b'c', 0, 0x7f, 2, 0, 0, 0];
+        let offset = &mut 0;
+        assert_eq!(bytes.gread_with::<Entry>(offset, LE).unwrap(), Entry { name: "a", size: 128, flags: 1 });
+        assert_eq!(*offset, 8);
+        assert_eq!(bytes.gread_with::<Entry>(offset, LE).unwrap(), Entry { name: "bc", size: 127, flags: 2 });
+        assert_eq!(*offset, 16);
+        assert!(bytes.gread_with::<Entry>(offset, LE).is_err());
+        assert_eq!(*offset, 16);
+        let entries: Vec<Entry> = bytes.pread_vec_with(0, 2, LE).unwrap();
+        assert_eq!(entries[1].name, "bc");
+    }
+
     /////////////////////////////////////////////////////////////////
     // end gread_with
     /////////////////////////////////////////////////////////////////
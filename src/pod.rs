@@ -0,0 +1,136 @@
+use core::mem;
+use core::slice;
+
+use endian::{Endian, NATIVE};
+use error;
+
+/// A plain old data type, which can be borrowed straight out of a byte buffer without copying, using [pread_ref](trait.Pread.html#method.pread_ref) for one, or [pread_slice](trait.Pread.html#method.pread_slice) for a `[T]`
+///
+/// Implemented for the primitive numbers and arrays of them. Implement it for your own `repr(C)` records to borrow them too.
+///
+/// Borrowing checks that the bytes are in range, that they are aligned in memory for `T`, and, since a reference can't be byte swapped,
+/// that the context is the native endianness, unless `T` reads the same in either byte order, like bytes or the fixed endian [U32Be](struct.U32Be.html) and friends.
+///
+/// # Safety
+///
+/// Implementors must be valid for every possible bit pattern of their size, e.g., no `bool`s, `char`s, enums or references,
+/// and must be `repr(C)` or `repr(transparent)` so their layout is defined.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pod, Pread, Error, NATIVE};
+///
+/// #[repr(C)]
+/// #[derive(Copy, Clone)]
+/// struct Entry {
+///     key: u32,
+///     value: u32,
+/// }
+///
+/// unsafe impl Pod for Entry {}
+///
+/// // a u64 buffer so the bytes are suitably aligned
+/// let words = [0u64; 4];
+/// let bytes: &[u8] = unsafe { ::std::slice::from_raw_parts(words.as_ptr() as *const u8, 32) };
+/// let entries = bytes.pread_slice::<[Entry]>(0, 4).unwrap();
+/// assert_eq!(entries.len(), 4);
+/// let index = bytes.pread_ref::<[u64; 2]>(16).unwrap();
+/// assert_eq!(*index, [0, 0]);
+/// match bytes.pread_ref::<u32>(2) {
+///     Err(Error::BadAlignment { offset: 2, align: 4 }) => (),
+///     res => panic!("expected a BadAlignment, got {:?}", res.map(|_| ())),
+/// }
+/// ```
+pub unsafe trait Pod: Copy + 'static {
+    /// Whether `Self` reads the same whatever the byte order, so it can be borrowed with any endianness
+    const ENDIAN_AGNOSTIC: bool = false;
+}
+
+macro_rules! pod_impl {
+    ($($typ:ty),*) => {
+        $(unsafe impl Pod for $typ {})*
+    }
+}
+
+pod_impl!(u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+unsafe impl Pod for u8 {
+    const ENDIAN_AGNOSTIC: bool = true;
+}
+
+unsafe impl Pod for i8 {
+    const ENDIAN_AGNOSTIC: bool = true;
+}
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {
+    const ENDIAN_AGNOSTIC: bool = T::ENDIAN_AGNOSTIC;
+}
+
+/// Borrows `count` `T`s at `offset` in `bytes`, checking the range, the alignment, and unless `T` is endian agnostic, that `endian` is native
+#[inline]
+pub(crate) fn slice_from_bytes<T: Pod>(bytes: &[u8], offset: usize, count: usize, endian: Endian) -> error::Result<&[T]> {
+    let size = mem::size_of::<T>();
+    let end = error::check_range(offset, count.saturating_mul(size), bytes.len())?;
+    let bytes = &bytes[offset..end];
+    let align = mem::align_of::<T>();
+    if bytes.as_ptr().align_offset(align) != 0 {
+        return Err(error::Error::BadAlignment { offset, align })
+    }
+    if !T::ENDIAN_AGNOSTIC && endian != NATIVE {
+        return Err(error::Error::BadInput { range: offset..end, size: end - offset, msg: "borrowing requires the native endianness" })
+    }
+    // the range is in bounds and aligned, and every bit pattern is a valid `T`
+    Ok(unsafe { slice::from_raw_parts(bytes.as_ptr() as *const T, count) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Pread, Error, NATIVE};
+    use super::super::ctx::TryRefFromCtx;
+
+    // u64s, so that the bytes start 8 byte aligned
+    fn aligned(words: &[u64]) -> &[u8] {
+        unsafe { ::core::slice::from_raw_parts(words.as_ptr() as *const u8, words.len() * 8) }
+    }
+
+    #[test]
+    fn pread_slices() {
+        let words = [1u64, 2, 3, 4];
+        let bytes = aligned(&words);
+        assert_eq!(bytes.pread_slice::<[u64]>(8, 3).unwrap(), &words[1..]);
+        assert_eq!(bytes.pread_slice::<[u64]>(32, 0).unwrap(), &[]);
+        assert_eq!(bytes.pread_slice::<[[u64; 2]]>(0, 2).unwrap(), &[[1, 2], [3, 4]]);
+        assert_eq!(bytes.pread_slice::<[u32]>(4, 7).unwrap().len(), 7);
+        // a byte slice has no alignment to check
+        assert_eq!(bytes.pread_slice::<[u8]>(3, 2).unwrap().len(), 2);
+        assert_eq!(*bytes.pread_ref::<[u8; 3]>(5).unwrap(), [0, 0, 0]);
+        match bytes.pread_slice::<[u64]>(8, 4) {
+            Err(Error::BadRange { range, size: 32 }) => assert_eq!(range, 8..40),
+            res => panic!("expected a BadRange, got {:?}", res),
+        }
+        assert!(bytes.pread_slice::<[u64]>(8, usize::max_value()).is_err());
+    }
+
+    #[test]
+    fn pread_ref_checks() {
+        let words = [0x0102030405060708u64, 0];
+        let bytes = aligned(&words);
+        assert_eq!(*bytes.pread_ref::<u64>(0).unwrap(), 0x0102030405060708);
+        assert_eq!(*bytes.pread_ref::<f64>(8).unwrap(), 0.0);
+        match bytes.pread_ref::<u16>(3) {
+            Err(Error::BadAlignment { offset: 3, align: 2 }) => (),
+            res => panic!("expected a BadAlignment, got {:?}", res),
+        }
+        match bytes.pread_ref::<u64>(12) {
+            Err(Error::BadRange { .. }) => (),
+            res => panic!("expected a BadRange, got {:?}", res),
+        }
+        match <[u32]>::try_ref_from_ctx(bytes, (0, 2, !NATIVE)) {
+            Err(Error::BadInput { range, .. }) => assert_eq!(range, 0..8),
+            res => panic!("expected a BadInput, got {:?}", res),
+        }
+        // bytes are the same in either order
+        assert!(<[u8]>::try_ref_from_ctx(bytes, (0, 2, !NATIVE)).is_ok());
+        assert!(<[[i8; 2]]>::try_ref_from_ctx(bytes, (0, 2, !NATIVE)).is_ok());
+    }
+}
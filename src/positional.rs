@@ -0,0 +1,352 @@
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, Read, Write, Seek, SeekFrom};
+
+use ctx::{self, FromCtx, IntoCtx, SizeWith, StrCtx, TryFromStream, TryIntoStream};
+use error;
+
+#[inline]
+fn advance(offset: u64, count: usize) -> io::Result<u64> {
+    offset.checked_add(count as u64).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflows a u64"))
+}
+
+/// A source of bytes which can be read at any `u64` offset without a shared cursor, like `pread(2)`.
+/// Only present when `std` feature is used (the default).
+///
+/// This is implemented for `File` on unix and windows, and for any `Read + Seek` stream wrapped in a [SeekAt](struct.SeekAt.html).
+/// The methods are prefixed with `scroll_` so they don't clash with `std::os::unix::fs::FileExt` when both are in scope.
+pub trait ReadAt {
+    /// Reads bytes at `offset` into `buf`, returning how many were read; `0` means `offset` is at, or past, the end
+    fn scroll_read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+    /// Fills `buf` with the bytes at `offset`, or fails with `UnexpectedEof` if there aren't enough
+    fn scroll_read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.scroll_read_at(buf, offset) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")),
+                Ok(count) => {
+                    buf = &mut buf[count..];
+                    offset = advance(offset, count)?;
+                },
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+    /// Reads `count` bytes at `offset` into a `Vec`; `count` isn't trusted to preallocate
+    #[inline]
+    fn pread_bytes_at(&self, offset: u64, count: usize) -> error::Result<Vec<u8>> {
+        Vec::try_from_stream(&mut Positioned { inner: self, offset }, StrCtx::Length(count))
+    }
+    /// Reads `count` bytes of utf8 at `offset` into a `String`
+    #[inline]
+    fn pread_string_at(&self, offset: u64, count: usize) -> error::Result<String> {
+        String::try_from_stream(&mut Positioned { inner: self, offset }, StrCtx::Length(count))
+    }
+}
+
+/// A sink of bytes which can be written at any `u64` offset without a shared cursor, like `pwrite(2)`.
+/// Only present when `std` feature is used (the default).
+pub trait WriteAt {
+    /// Writes bytes from `buf` at `offset`, returning how many were written
+    fn scroll_write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize>;
+    /// Writes all of `buf` at `offset`
+    fn scroll_write_all_at(&self, mut buf: &[u8], mut offset: u64) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.scroll_write_at(buf, offset) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer")),
+                Ok(count) => {
+                    buf = &buf[count..];
+                    offset = advance(offset, count)?;
+                },
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    #[inline]
+    fn scroll_read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).scroll_read_at(buf, offset)
+    }
+}
+
+impl<T: WriteAt + ?Sized> WriteAt for &T {
+    #[inline]
+    fn scroll_write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        (**self).scroll_write_at(buf, offset)
+    }
+}
+
+#[cfg(unix)]
+impl ReadAt for File {
+    #[inline]
+    fn scroll_read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        ::std::os::unix::fs::FileExt::read_at(self, buf, offset)
+    }
+}
+
+#[cfg(unix)]
+impl WriteAt for File {
+    #[inline]
+    fn scroll_write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        ::std::os::unix::fs::FileExt::write_at(self, buf, offset)
+    }
+}
+
+// NB: unlike on unix, these move the file's cursor
+#[cfg(windows)]
+impl ReadAt for File {
+    #[inline]
+    fn scroll_read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        ::std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+    }
+}
+
+#[cfg(windows)]
+impl WriteAt for File {
+    #[inline]
+    fn scroll_write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        ::std::os::windows::fs::FileExt::seek_write(self, buf, offset)
+    }
+}
+
+/// Adapts a `Read + Seek` stream into a [ReadAt](trait.ReadAt.html), and a `Write + Seek` stream into a [WriteAt](trait.WriteAt.html), by seeking before every access.
+/// Only present when `std` feature is used (the default).
+///
+/// The stream's cursor is left wherever the last access ended.
+///
+/// # Example
+/// ```rust
+/// use std::io::Cursor;
+/// use scroll::{SeekAt, PreadAt, PwriteAt, BE};
+/// let stream = SeekAt::new(Cursor::new(vec![0u8; 8]));
+/// stream.pwrite_at_with(0xdeadbeefu32, 4, BE).unwrap();
+/// assert_eq!(stream.pread_at_with::<u16>(6, BE).unwrap(), 0xbeef);
+/// assert_eq!(stream.into_inner().into_inner(), [0, 0, 0, 0, 0xde, 0xad, 0xbe, 0xef]);
+/// ```
+#[derive(Debug, Default)]
+pub struct SeekAt<T> {
+    inner: RefCell<T>,
+}
+
+impl<T> SeekAt<T> {
+    pub fn new(inner: T) -> Self {
+        SeekAt { inner: RefCell::new(inner) }
+    }
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+    /// Consumes self and returns the inner stream
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: Read + Seek> ReadAt for SeekAt<T> {
+    fn scroll_read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let mut inner = self.inner.borrow_mut();
+        inner.seek(SeekFrom::Start(offset))?;
+        inner.read(buf)
+    }
+}
+
+impl<T: Write + Seek> WriteAt for SeekAt<T> {
+    fn scroll_write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let mut inner = self.inner.borrow_mut();
+        inner.seek(SeekFrom::Start(offset))?;
+        inner.write(buf)
+    }
+}
+
+/// A `Read`/`Write` stream over a `ReadAt`/`WriteAt` which starts at `offset`
+struct Positioned<'a, T: ?Sized + 'a> {
+    inner: &'a T,
+    offset: u64,
+}
+
+impl<'a, T: ReadAt + ?Sized> Read for Positioned<'a, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.scroll_read_at(buf, self.offset)?;
+        self.offset = advance(self.offset, count)?;
+        Ok(count)
+    }
+}
+
+impl<'a, T: WriteAt + ?Sized> Write for Positioned<'a, T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = self.inner.scroll_write_at(buf, self.offset)?;
+        self.offset = advance(self.offset, count)?;
+        Ok(count)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An extension trait to [ReadAt](trait.ReadAt.html) sources, like a `File`, for reading owned values at `u64` offsets, without reading the whole source into memory first.
+/// Only present when `std` feature is used (the default).
+///
+/// Since nothing can be borrowed from the source, fixed size `FromCtx + SizeWith` types are returned by value, and slices and strings as a `Vec<u8>` or `String`.
+/// Everything fails with a scroll `Error`, io errors included, as `Error::IO`, like the `ReadAt` helpers `pread_bytes_at` and `pread_string_at`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{ctx, ReadAt, PreadAt, PwriteAt, Uleb128, LE};
+/// # let path = ::std::env::temp_dir().join(format!("scroll-preadat-{}", ::std::process::id()));
+/// let file = ::std::fs::OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path).unwrap();
+/// file.pwrite_at_with(0xfeedfacfu32, 0x1_0000, LE).unwrap();
+/// file.pwrite_try_at_with("core", 0x1_0004, ctx::NULL).unwrap();
+/// file.pwrite_try_at(Uleb128::new(624485), 0x1_0009).unwrap();
+/// assert_eq!(file.pread_at_with::<u32>(0x1_0000, LE).unwrap(), 0xfeedfacf);
+/// assert_eq!(file.pread_try_at_with::<String>(0x1_0004, ctx::NULL).unwrap(), "core");
+/// assert_eq!(u64::from(file.pread_try_at::<Uleb128>(0x1_0009).unwrap()), 624485);
+/// assert_eq!(file.pread_bytes_at(0x1_0004, 4).unwrap(), b"core");
+/// // reading past the end is an `UnexpectedEof` io error
+/// assert!(file.pread_at::<u64>(0x1_0008).is_err());
+/// # drop(file);
+/// # ::std::fs::remove_file(&path).unwrap();
+/// ```
+pub trait PreadAt<Ctx = super::Endian>: ReadAt
+    where Ctx: Copy + Default + Debug,
+{
+    /// Reads an `N` at `offset` with a default `Ctx`
+    #[inline]
+    fn pread_at<N: FromCtx<Ctx> + SizeWith<Ctx, Units = usize>>(&self, offset: u64) -> error::Result<N> {
+        self.pread_at_with(offset, Ctx::default())
+    }
+    /// Reads an `N` at `offset` with `ctx`.
+    /// **NB**: this allocates if the type you're reading has a size greater than 256.
+    #[inline]
+    fn pread_at_with<N: FromCtx<Ctx> + SizeWith<Ctx, Units = usize>>(&self, offset: u64, ctx: Ctx) -> error::Result<N> {
+        ctx::with_scratch(N::size_with(&ctx), |buf| {
+            self.scroll_read_exact_at(buf, offset)?;
+            Ok(N::from_ctx(buf, ctx))
+        })
+    }
+    /// Tries to read an `N` at `offset` with a default `Ctx`; see [pread_try_at_with](#method.pread_try_at_with)
+    #[inline]
+    fn pread_try_at<N: TryFromStream<Ctx, Error = error::Error>>(&self, offset: u64) -> error::Result<N> {
+        self.pread_try_at_with(offset, Ctx::default())
+    }
+    /// Tries to read an `N` at `offset` with `ctx`, where `N` is any [TryFromStream](ctx/trait.TryFromStream.html),
+    /// e.g., a `Uleb128`, an owned `String` or `Vec<u8>` with a `StrCtx`, or any `TryFromCtx + SizeWith` type.
+    ///
+    /// **NB**: variable length types may read a byte at a time, which is a syscall each for a `File`
+    #[inline]
+    fn pread_try_at_with<N: TryFromStream<Ctx, Error = error::Error>>(&self, offset: u64, ctx: Ctx) -> error::Result<N> {
+        N::try_from_stream(&mut Positioned { inner: self, offset }, ctx)
+    }
+}
+
+/// Types that implement `ReadAt` get methods defined in `PreadAt`
+/// for free.
+impl<Ctx: Copy + Default + Debug, R: ReadAt + ?Sized> PreadAt<Ctx> for R {}
+
+/// An extension trait to [WriteAt](trait.WriteAt.html) sinks, like a `File`, for writing values at `u64` offsets.
+/// Only present when `std` feature is used (the default).
+///
+/// Writing past the end of a `File` extends it.
+pub trait PwriteAt<Ctx = super::Endian>: WriteAt
+    where Ctx: Copy + Default + Debug,
+{
+    /// Writes `n` at `offset` with a default `Ctx`
+    #[inline]
+    fn pwrite_at<N: IntoCtx<Ctx> + SizeWith<Ctx, Units = usize>>(&self, n: N, offset: u64) -> error::Result<()> {
+        self.pwrite_at_with(n, offset, Ctx::default())
+    }
+    /// Writes `n` at `offset` with `ctx`.
+    /// **NB**: this allocates if the type you're writing has a size greater than 256.
+    #[inline]
+    fn pwrite_at_with<N: IntoCtx<Ctx> + SizeWith<Ctx, Units = usize>>(&self, n: N, offset: u64, ctx: Ctx) -> error::Result<()> {
+        ctx::with_scratch(N::size_with(&ctx), |buf| {
+            n.into_ctx(buf, ctx);
+            self.scroll_write_all_at(buf, offset)?;
+            Ok(())
+        })
+    }
+    /// Tries to write `n` at `offset` with a default `Ctx`; see [pwrite_try_at_with](#method.pwrite_try_at_with)
+    #[inline]
+    fn pwrite_try_at<N: TryIntoStream<Ctx, Error = error::Error>>(&self, n: N, offset: u64) -> error::Result<()> {
+        self.pwrite_try_at_with(n, offset, Ctx::default())
+    }
+    /// Tries to write `n` at `offset` with `ctx`, where `N` is any [TryIntoStream](ctx/trait.TryIntoStream.html),
+    /// e.g., a `Uleb128`, a `&str` or `&[u8]` with a `StrCtx`, or any `TryIntoCtx + SizeWith` type
+    #[inline]
+    fn pwrite_try_at_with<N: TryIntoStream<Ctx, Error = error::Error>>(&self, n: N, offset: u64, ctx: Ctx) -> error::Result<()> {
+        n.try_into_stream(&mut Positioned { inner: self, offset }, ctx)
+    }
+}
+
+/// Types that implement `WriteAt` get methods defined in `PwriteAt`
+/// for free.
+impl<Ctx: Copy + Default + Debug, W: WriteAt + ?Sized> PwriteAt<Ctx> for W {}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor};
+    use super::{SeekAt, ReadAt, WriteAt, PreadAt, PwriteAt};
+    use super::super::{ctx, Error, Uleb128, LE, BE};
+
+    #[test]
+    fn seek_at_roundtrip() {
+        let stream = SeekAt::new(Cursor::new(Vec::new()));
+        stream.pwrite_at_with(0xdeadbeefu32, 2, BE).unwrap();
+        stream.pwrite_try_at_with("hi", 6, ctx::NULL).unwrap();
+        stream.pwrite_try_at(Uleb128::new(300), 9).unwrap();
+        stream.pwrite_at([1u16, 2], 11).unwrap();
+        assert_eq!(stream.pread_at_with::<u32>(2, BE).unwrap(), 0xdeadbeef);
+        assert_eq!(stream.pread_at_with::<u16>(2, LE).unwrap(), 0xadde);
+        assert_eq!(stream.pread_try_at_with::<String>(6, ctx::NULL).unwrap(), "hi");
+        assert_eq!(stream.pread_try_at_with::<Vec<u8>>(6, ctx::StrCtx::Length(2)).unwrap(), b"hi");
+        assert_eq!(u64::from(stream.pread_try_at::<Uleb128>(9).unwrap()), 300);
+        assert_eq!(stream.pread_at::<[u16; 2]>(11).unwrap(), [1, 2]);
+        assert_eq!(stream.pread_bytes_at(0, 2).unwrap(), [0, 0]);
+        assert_eq!(stream.pread_string_at(6, 2).unwrap(), "hi");
+        assert_eq!(stream.into_inner().into_inner().len(), 15);
+    }
+
+    #[test]
+    fn read_past_end() {
+        let stream = SeekAt::new(Cursor::new([1u8, 2, 3]));
+        let mut buf = [0u8; 2];
+        assert_eq!(stream.scroll_read_at(&mut buf, 2).unwrap(), 1);
+        assert_eq!(stream.scroll_read_at(&mut buf, 3).unwrap(), 0);
+        match stream.pread_at::<u16>(2) {
+            Err(Error::IO(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof => (),
+            res => panic!("{:?}", res),
+        }
+        // a huge count is neither trusted nor preallocated
+        assert!(stream.pread_bytes_at(1, ::std::usize::MAX).is_err());
+        assert_eq!(stream.pread_string_at(0, 3).unwrap(), "\u{1}\u{2}\u{3}");
+        assert!(stream.scroll_read_exact_at(&mut buf, ::std::u64::MAX).is_err());
+    }
+
+    #[test]
+    #[cfg(any(unix, windows))]
+    fn file() {
+        use std::fs::OpenOptions;
+        let path = ::std::env::temp_dir().join(format!("scroll-positional-{}", ::std::process::id()));
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path).unwrap();
+        // above 4GiB would need a sparse file system; this is far enough to not be a single read
+        file.pwrite_at_with(0xcafebabeu32, 0x10_0000, BE).unwrap();
+        file.scroll_write_all_at(b"elf", 1).unwrap();
+        assert_eq!(file.pread_at_with::<u32>(0x10_0000, BE).unwrap(), 0xcafebabe);
+        assert_eq!(file.pread_bytes_at(0, 4).unwrap(), b"\0elf");
+        assert_eq!((&file).pread_at::<u8>(2).unwrap(), b'l');
+        assert!(file.pread_at::<u16>(0x10_0003).is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileExt;
+            let mut buf = [0u8; 3];
+            file.read_exact_at(&mut buf, 1).unwrap();
+            assert_eq!(&buf, b"elf");
+        }
+        drop(file);
+        ::std::fs::remove_file(&path).unwrap();
+    }
+}
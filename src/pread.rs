@@ -1,9 +1,59 @@
 use core::result;
 use core::fmt::Debug;
+use core::mem::size_of;
+use core::convert::TryFrom;
 
-use ctx::{TryFromCtx, TryRefFromCtx};
+use ctx::{TryFromCtx, TryReadCtx, TryRefFromCtx};
+use pod::Pod;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use error;
-use endian::Endian;
+use endian::{Endian, NATIVE};
+
+/// An unsigned integer type that offsets can be given in, e.g., a `u64` file offset on a 32-bit host, or a `u32` to keep a parser's tables small
+///
+/// The byte slice impls of `Pread`, `Pwrite`, `Gread` and `Gwrite` index with `usize`s; their `_offset` methods, e.g., [pread_offset_with](trait.Pread.html#method.pread_offset_with), take any of these instead,
+/// and convert them to a `usize` slice index before reading or writing, so every `TryFromCtx` and `TryIntoCtx` impl works with them.
+/// `gread_offset` and `gwrite_offset` convert the updated offset back again.
+/// An offset which doesn't fit is an `Error::BadOffsetType`, never a truncation.
+///
+/// Your own target can also implement the traits with an `Offset` as `I` and a `(usize, Ctx)` `TryCtx`, converting the offset in `pread_with` and `pwrite_with`;
+/// `Gread` and `Gwrite` then convert the incremented offset back to an `I` themselves.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Pread, Gread, BE};
+/// let bytes = [0xde, 0xad, 0xbe, 0xef];
+/// let offset: u64 = 2;
+/// let beef: u16 = bytes.pread_offset_with(offset, BE).unwrap();
+/// assert_eq!(beef, 0xbeef);
+/// let offset = &mut 0u32;
+/// let dead: u16 = bytes.gread_offset_with(offset, BE).unwrap();
+/// assert_eq!((dead, *offset), (0xdead, 2));
+/// ```
+pub trait Offset: Copy + Debug {
+    /// Converts the offset to a slice index
+    fn try_into_index(self) -> error::Result<usize>;
+    /// Converts a slice index back into an offset
+    fn try_from_index(index: usize) -> error::Result<Self>;
+}
+
+macro_rules! offset_impl {
+    ($($typ:ty),+) => {
+        $(impl Offset for $typ {
+            #[inline]
+            fn try_into_index(self) -> error::Result<usize> {
+                usize::try_from(self).map_err(|_| error::Error::BadOffsetType { offset: self as i128, ty: "usize" })
+            }
+            #[inline]
+            fn try_from_index(index: usize) -> error::Result<Self> {
+                <$typ>::try_from(index).map_err(|_| error::Error::BadOffsetType { offset: index as i128, ty: stringify!($typ) })
+            }
+        })+
+    }
+}
+
+offset_impl!(usize, u8, u16, u32, u64);
 
 /// A very generic, contextual pread interface in Rust. Allows completely parallelized reads, as `Self` is immutable
 ///
@@ -117,7 +167,49 @@ pub trait Pread<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), Sl
     /// let dead: u16 = bytes.pread_with(0, scroll::BE).unwrap();
     /// assert_eq!(dead, 0xdeadu16);
     fn pread_with<'a, N: TryFromCtx<'a, TryCtx, Error = E>>(&'a self, offset: I, ctx: Ctx) -> result::Result<N, E>;
+    /// Reads a value from `self` at `offset` with the given `ctx`, along with how many bytes it consumed
+    ///
+    /// `N` can be variable length, e.g., a `Uleb128`, or a `&str` with a `StrCtx`; `Gread` advances its offset by the size this returns.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, Uleb128, LE};
+    /// let bytes = [0xff, 0xe5, 0x8e, 0x26];
+    /// let (uleb, size): (Uleb128, usize) = bytes.pread_sized_with(1, LE).unwrap();
+    /// assert_eq!((u64::from(uleb), size), (624485, 3));
+    /// ```
+    #[inline]
+    fn pread_sized_with<'a, N: TryReadCtx<'a, TryCtx, Error = E>>(&'a self, offset: I, ctx: Ctx) -> result::Result<(N, usize), E> {
+        let WithSize(n, size) = self.pread_with(offset, ctx)?;
+        Ok((n, size))
+    }
+    /// Reads a value from `self` at an `offset` of any [Offset](trait.Offset.html) type, e.g., a `u64`, with a default `Ctx`
+    #[inline]
+    fn pread_offset<'a, N, O>(&'a self, offset: O) -> result::Result<N, E>
+        where N: TryFromCtx<'a, TryCtx, Error = E>,
+              O: Offset,
+              I: Offset,
+              E: From<error::Error> {
+        self.pread_offset_with(offset, Ctx::default())
+    }
+    /// Reads a value from `self` at an `offset` of any [Offset](trait.Offset.html) type, e.g., a `u64`, with the given `ctx`; an offset which doesn't fit in a `usize` is a `BadOffsetType`
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, BE};
+    /// let bytes = [0xde, 0xad, 0xbe, 0xef];
+    /// let beef: u16 = bytes.pread_offset_with(2u64, BE).unwrap();
+    /// assert_eq!(beef, 0xbeef);
+    /// ```
+    #[inline]
+    fn pread_offset_with<'a, N, O>(&'a self, offset: O, ctx: Ctx) -> result::Result<N, E>
+        where N: TryFromCtx<'a, TryCtx, Error = E>,
+              O: Offset,
+              I: Offset,
+              E: From<error::Error> {
+        self.pread_with(I::try_from_index(offset.try_into_index()?)?, ctx)
+    }
     /// Slices an `N` from `self` at `offset` up to `count` times
+    ///
+    /// Besides `str` and `[u8]`, this borrows a `[T]` of any [Pod](trait.Pod.html) type, e.g., a `[u64]` index, without copying; `count` is the number of `T`s
     #[inline]
     /// # Example
     /// ```rust
@@ -128,6 +220,149 @@ pub trait Pread<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), Sl
     /// let bytes2 = bytes.pread_slice::<[u8]>(0, 2).unwrap();
     /// assert_eq!(bytes, bytes2);
     fn pread_slice<'a, N: ?Sized + TryRefFromCtx<SliceCtx, Error = E>>(&'a self, offset: I, count: I) -> result::Result<&'a N, E>;
+    /// Borrows a [Pod](trait.Pod.html) `N`, e.g., a `u64` or a `repr(C)` record, from `self` at `offset` without copying
+    ///
+    /// Errors if the bytes are out of range, if they aren't aligned in memory for `N` (a `BadAlignment`), or if the default `Ctx` isn't the native endianness
+    /// # Example
+    /// ```rust
+    /// use scroll::Pread;
+    /// let words = [0x0102030405060708u64, 0];
+    /// let bytes: &[u8] = unsafe { ::std::slice::from_raw_parts(words.as_ptr() as *const u8, 16) };
+    /// assert_eq!(*bytes.pread_ref::<u64>(0).unwrap(), 0x0102030405060708);
+    /// assert_eq!(*bytes.pread_ref::<u32>(8).unwrap(), 0);
+    /// assert!(bytes.pread_ref::<u32>(1).is_err());
+    /// assert!(bytes.pread_ref::<u64>(16).is_err());
+    /// ```
+    #[inline]
+    fn pread_ref<N: Pod>(&self, offset: I) -> result::Result<&N, E>
+        where [N]: TryRefFromCtx<SliceCtx, Error = E>,
+              I: Offset,
+              E: From<error::Error> {
+        let slice = self.pread_slice::<[N]>(offset, I::try_from_index(1)?)?;
+        Ok(&slice[0])
+    }
+    /// Probes for a byte order mark, or magic number, at `offset` by reading an `N` in both byte orders, and returns the first of `magics` which matches, along with the detected endianness.
+    /// Errors with `BadInput` if none of `magics` match.
+    ///
+    /// If a magic reads the same in both byte orders, the native byte order is returned.
+    /// # Example
+    /// ```rust
+    /// use scroll::{Pread, BE};
+    /// const MH_MAGIC: u32 = 0xfeedface;
+    /// const MH_MAGIC_64: u32 = 0xfeedfacf;
+    /// let bytes = [0xfe, 0xed, 0xfa, 0xcf, 0x01, 0x00, 0x00, 0x07];
+    /// let (magic, endian) = bytes.pread_bom(0, &[MH_MAGIC, MH_MAGIC_64]).unwrap();
+    /// assert_eq!(magic, MH_MAGIC_64);
+    /// assert_eq!(endian, BE);
+    /// let cputype: u32 = bytes.pread_with(4, endian).unwrap();
+    /// assert_eq!(cputype, 0x01000007);
+    /// assert!(bytes.pread_bom(4, &[MH_MAGIC, MH_MAGIC_64]).is_err());
+    /// ```
+    #[inline]
+    fn pread_bom<'a, N>(&'a self, offset: I, magics: &[N]) -> result::Result<(N, Endian), E>
+        where N: TryFromCtx<'a, TryCtx, Error = E> + PartialEq,
+              Ctx: From<Endian>,
+              E: From<error::Error>,
+              I: Offset {
+        let native: N = self.pread_with(offset, Ctx::from(NATIVE))?;
+        let swapped: N = self.pread_with(offset, Ctx::from(!NATIVE))?;
+        for magic in magics {
+            if native == *magic {
+                return Ok((native, NATIVE))
+            }
+            if swapped == *magic {
+                return Ok((swapped, !NATIVE))
+            }
+        }
+        let offset = offset.try_into_index()?;
+        let size = size_of::<N>();
+        Err(error::Error::BadInput { range: offset..offset+size, size, msg: "unknown byte order mark" }.into())
+    }
+    /// Reads `count` `N`s from `self` starting at `offset` with a default `Ctx`, one after another, into a `Vec`. Only present when `alloc` feature is used (the default).
+    /// # Example
+    /// ```rust
+    /// use scroll::Pread;
+    /// let bytes = [1u8, 2, 3, 4];
+    /// let vec: Vec<u8> = bytes.pread_vec(1, 3).unwrap();
+    /// assert_eq!(vec, [2, 3, 4]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn pread_vec<'a, N>(&'a self, offset: I, count: usize) -> result::Result<Vec<N>, E>
+        where Self: AsRef<[u8]>,
+              N: TryReadCtx<'a, (usize, Ctx), Error = E>,
+              E: From<error::Error>,
+              I: Offset {
+        self.pread_vec_with(offset, count, Ctx::default())
+    }
+    /// Reads `count` `N`s from `self` starting at `offset` with `ctx`, one after another, into a `Vec`. Only present when `alloc` feature is used (the default).
+    ///
+    /// `N` can be variable length, e.g., a `Uleb128` or a `&str`, in which case each item is read where the previous one ended.
+    /// If `N` has a fixed size, like the primitives or anything implementing `SizeWith`, the whole table is bounds checked before anything is read.
+    /// An item which takes up no bytes, e.g., a `StrCtx::Length(0)` string, is a `BadInput` error, as `count` could otherwise never be checked against the input;
+    /// this holds even when `count` is 1, so read a lone empty item with `pread_with` instead.
+    /// # Example
+    /// ```rust
+    /// use scroll::{ctx, Pread, BE};
+    /// let bytes = [0xde, 0xad, 0xbe, 0xef, b'h', b'i', 0, b'y', b'o', 0];
+    /// let halves: Vec<u16> = bytes.pread_vec_with(0, 2, BE).unwrap();
+    /// assert_eq!(halves, [0xdead, 0xbeef]);
+    /// let strings: Vec<&str> = bytes.pread_vec_with(4, 2, ctx::NULL).unwrap();
+    /// assert_eq!(strings, ["hi", "yo"]);
+    /// // there aren't 6 `u16`s in 10 bytes, which is caught before reading any of them
+    /// assert!(bytes.pread_vec_with::<u16>(0, 6, BE).is_err());
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn pread_vec_with<'a, N>(&'a self, offset: I, count: usize, ctx: Ctx) -> result::Result<Vec<N>, E>
+        where Self: AsRef<[u8]>,
+              N: TryReadCtx<'a, (usize, Ctx), Error = E>,
+              E: From<error::Error>,
+              I: Offset {
+        read_vec(self.as_ref(), &mut offset.try_into_index()?, count, ctx)
+    }
+}
+
+/// An `N` along with how many bytes it was read from, so that `pread_sized_with` can go through `pread_with`
+struct WithSize<N>(N, usize);
+
+impl<'a, Ctx: Copy, N: TryReadCtx<'a, Ctx>> TryFromCtx<'a, Ctx> for WithSize<N> {
+    type Error = N::Error;
+    #[inline]
+    fn try_from_ctx(from: &'a [u8], ctx: Ctx) -> result::Result<Self, Self::Error> {
+        let (n, size) = N::try_read_ctx(from, ctx)?;
+        Ok(WithSize(n, size))
+    }
+}
+
+/// Reads `count` `N`s from `bytes` one after another, starting at `offset`, and only updates `offset` to the end of the last one if they were all read
+#[cfg(feature = "alloc")]
+pub(crate) fn read_vec<'a, Ctx, N, E>(bytes: &'a [u8], offset: &mut usize, count: usize, ctx: Ctx) -> result::Result<Vec<N>, E>
+    where Ctx: Copy,
+          N: TryReadCtx<'a, (usize, Ctx), Error = E>,
+          E: From<error::Error> {
+    let mut o = *offset;
+    let len = bytes.len();
+    let capacity = match N::fixed_size(&(o, ctx)) {
+        Some(size) => {
+            // a saturated table size can't fit in any slice, so it still fails the check
+            error::check_range(o, size.saturating_mul(count), len)?;
+            count
+        },
+        // don't trust `count` for the allocation, but every item should take up at least a byte
+        None => count.min(len.saturating_sub(o)),
+    };
+    let mut vec = Vec::with_capacity(capacity);
+    for _ in 0..count {
+        let (n, size) = N::try_read_ctx(bytes, (o, ctx))?;
+        if size == 0 {
+            return Err(error::Error::BadInput { range: o..o, size: len, msg: "item takes up no bytes" }.into())
+        }
+        vec.push(n);
+        o = o.saturating_add(size);
+    }
+    *offset = o;
+    Ok(vec)
 }
 
 impl<Ctx, E> Pread<Ctx, E> for [u8]
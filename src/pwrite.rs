@@ -1,9 +1,10 @@
 use core::result;
 use core::fmt::Debug;
 
-use ctx::{TryIntoCtx};
+use ctx::{TryIntoCtx, TryWriteCtx};
 use error;
 use endian::Endian;
+use pread::Offset;
 
 /// Writes into `Self` at an offset of type `I` using a `Ctx`
 ///
@@ -54,9 +55,62 @@ pub trait Pwrite<Ctx = Endian, E = error::Error, I = usize, TryCtx = (I, Ctx), S
     /// bytes.pwrite_with::<u32>(0xbeefbeef, 0, LE).unwrap();
     /// assert_eq!(bytes.pread_with::<u32>(0, LE).unwrap(), 0xbeefbeef);
     fn pwrite_with<N: TryIntoCtx<TryCtx, Error = E>>(&mut self, n: N, offset: I, ctx: Ctx) -> result::Result<(), E>;
+    /// Write `N` at offset `I` with context `Ctx`, and return how many bytes it wrote
+    ///
+    /// `N` can be variable length, e.g., a `Uleb128`, a `&[u8]` or a `&str`; `Gwrite` advances its offset by the size this returns.
+    /// # Example
+    /// ```
+    /// use scroll::{Pwrite, Uleb128, LE};
+    /// let mut bytes = [0u8; 4];
+    /// assert_eq!(bytes.pwrite_sized_with(Uleb128::new(300), 1, LE).unwrap(), 2);
+    /// assert_eq!(bytes, [0, 0xac, 0x02, 0]);
+    /// ```
+    #[inline]
+    fn pwrite_sized_with<N: TryWriteCtx<TryCtx, Error = E>>(&mut self, n: N, offset: I, ctx: Ctx) -> result::Result<usize, E> {
+        let mut size = 0;
+        self.pwrite_with(WithSize(n, &mut size), offset, ctx)?;
+        Ok(size)
+    }
+    /// Write `N` at an `offset` of any [Offset](trait.Offset.html) type, e.g., a `u64`, with a default `Ctx`
+    #[inline]
+    fn pwrite_offset<N, O>(&mut self, n: N, offset: O) -> result::Result<(), E>
+        where N: TryIntoCtx<TryCtx, Error = E>,
+              O: Offset,
+              I: Offset,
+              E: From<error::Error> {
+        self.pwrite_offset_with(n, offset, Ctx::default())
+    }
+    /// Write `N` at an `offset` of any [Offset](trait.Offset.html) type, e.g., a `u64`, with context `Ctx`; an offset which doesn't fit in a `usize` is a `BadOffsetType`
+    /// # Example
+    /// ```
+    /// use scroll::{Pwrite, LE};
+    /// let mut bytes = [0u8; 4];
+    /// bytes.pwrite_offset_with(0xbeefu16, 2u64, LE).unwrap();
+    /// assert_eq!(bytes, [0, 0, 0xef, 0xbe]);
+    /// ```
+    #[inline]
+    fn pwrite_offset_with<N, O>(&mut self, n: N, offset: O, ctx: Ctx) -> result::Result<(), E>
+        where N: TryIntoCtx<TryCtx, Error = E>,
+              O: Offset,
+              I: Offset,
+              E: From<error::Error> {
+        self.pwrite_with(n, I::try_from_index(offset.try_into_index()?)?, ctx)
+    }
     //fn pwrite_slice<N: ?Sized + TrySliceFromCtx<SliceCtx, Error = E>>(&self, offset: I, count: I) -> result::Result<&N, E>;
 }
 
+/// An `N` which records how many bytes it wrote, so that `pwrite_sized_with` can go through `pwrite_with`
+struct WithSize<'s, N>(N, &'s mut usize);
+
+impl<'s, Ctx: Copy, N: TryWriteCtx<Ctx>> TryIntoCtx<Ctx> for WithSize<'s, N> {
+    type Error = N::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: Ctx) -> result::Result<(), Self::Error> {
+        *self.1 = self.0.try_write_ctx(dst, ctx)?;
+        Ok(())
+    }
+}
+
 impl<Ctx, E> Pwrite<Ctx, E> for [u8]
     where
     E: Debug,
@@ -0,0 +1,530 @@
+#[cfg(feature = "alloc")]
+use core::cell::RefCell;
+
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use align::padding;
+use ctx::{self, StrCtx, TryReadCtx};
+#[cfg(feature = "alloc")]
+use ctx::{DefaultCtx, TryIntoCtx, TryWriteCtx};
+use error;
+
+/// Shifts the offsets in `err` by `base`, so they point into the bytes a table was parsed from, rather than into the table
+fn rebase(err: error::Error, base: usize) -> error::Error {
+    use error::Error::*;
+    let shift = |range: ::core::ops::Range<usize>| range.start.saturating_add(base)..range.end.saturating_add(base);
+    match err {
+        BadOffset(offset) => BadOffset(offset.saturating_add(base)),
+        BadRange { range, size } => BadRange { range: shift(range), size },
+        BadInput { range, size, msg } => BadInput { range: shift(range), size, msg },
+        Overflow { offset, size } => Overflow { offset: offset.saturating_add(base), size },
+        err => err,
+    }
+}
+
+/// A string table: a blob of strings, e.g., an ELF `.strtab` or DWARF `.debug_str`, which other structures refer to by their offset into it
+///
+/// Strings are read lazily, as `&str`s borrowed from the blob, using a `StrCtx`; usually `ctx::NULL`, for NUL terminated strings.
+/// When the table is [parsed](#method.parse) out of a larger buffer, e.g., a whole file, errors report offsets in that buffer, so they can be found with a hex editor.
+///
+/// For strings listed back to back until an empty one, like a Windows `REG_MULTI_SZ` or environment block, see [multi](#method.multi).
+///
+/// # Example
+/// ```rust
+/// use scroll::{Strtab, ctx};
+/// let file = b"\x7fHDR\0printf\0puts\0";
+/// let strtab = Strtab::parse(file, 5, 12, ctx::NULL).unwrap();
+/// assert_eq!(strtab.get(0).unwrap(), "printf");
+/// assert_eq!(strtab.get(7).unwrap(), "puts");
+/// // "tf", a suffix of "printf"
+/// assert_eq!(strtab.get(4).unwrap(), "tf");
+/// let entries: Vec<(usize, &str)> = strtab.iter().map(|entry| entry.unwrap()).collect();
+/// assert_eq!(entries, [(0, "printf"), (7, "puts")]);
+/// // past the end of the table, at offset 12 + 5 in the file
+/// assert_eq!(strtab.get(12).unwrap_err().to_string(), "bad offset 17");
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct Strtab<'a> {
+    bytes: &'a [u8],
+    base: usize,
+    ctx: StrCtx,
+}
+
+impl<'a> Strtab<'a> {
+    /// Creates a table of NUL terminated strings from all of `bytes`
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self::with_ctx(bytes, ctx::NULL)
+    }
+    /// Creates a table from all of `bytes`, whose strings are read with `ctx`
+    pub fn with_ctx(bytes: &'a [u8], ctx: StrCtx) -> Self {
+        Strtab { bytes, base: 0, ctx }
+    }
+    /// Creates a table from the `len` bytes at `offset` in `bytes`, whose strings are read with `ctx`; errors report offsets in `bytes`
+    pub fn parse(bytes: &'a [u8], offset: usize, len: usize, ctx: StrCtx) -> error::Result<Self> {
+        let end = error::check_range(offset, len, bytes.len())?;
+        Ok(Strtab { bytes: &bytes[offset..end], base: offset, ctx })
+    }
+    /// The size of the table in bytes
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    /// The raw bytes of the table
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+    /// Reads the string at `offset`, and how many bytes it occupies
+    fn read(&self, offset: usize) -> error::Result<(&'a str, usize)> {
+        <&str>::try_read_ctx(self.bytes, (offset, self.ctx)).map_err(|err| rebase(err, self.base))
+    }
+    /// Gets the string at `offset` into the table
+    #[inline]
+    pub fn get(&self, offset: usize) -> error::Result<&'a str> {
+        self.read(offset).map(|(string, _)| string)
+    }
+    /// Iterates over every string in the table, along with its offset into the table; iteration stops after the first error
+    pub fn iter(&self) -> StrtabIter<'a> {
+        StrtabIter { strtab: *self, offset: 0, until_empty: false }
+    }
+    /// Iterates over the strings starting at `offset` which are listed back to back, up to the first empty string, or the end of the table
+    ///
+    /// With `ctx::NULL`, this is a list terminated by a double NUL.
+    /// # Example
+    /// ```rust
+    /// use scroll::Strtab;
+    /// let environment = Strtab::new(b"HOME=/root\0TERM=xterm\0\0garbage");
+    /// let vars: Result<Vec<&str>, _> = environment.multi(0).map(|var| var.map(|(_, var)| var)).collect();
+    /// assert_eq!(vars.unwrap(), ["HOME=/root", "TERM=xterm"]);
+    /// ```
+    pub fn multi(&self, offset: usize) -> StrtabIter<'a> {
+        StrtabIter { strtab: *self, offset, until_empty: true }
+    }
+    /// Wraps the table in a cache which remembers every string it has looked up. Only present when `alloc` feature is used (the default).
+    #[cfg(feature = "alloc")]
+    pub fn cached(self) -> CachedStrtab<'a> {
+        CachedStrtab { strtab: self, cache: RefCell::new(BTreeMap::new()) }
+    }
+}
+
+impl<'a> IntoIterator for &Strtab<'a> {
+    type Item = error::Result<(usize, &'a str)>;
+    type IntoIter = StrtabIter<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the strings in a [Strtab](struct.Strtab.html), and their offsets into it
+#[derive(Debug, Clone)]
+pub struct StrtabIter<'a> {
+    strtab: Strtab<'a>,
+    offset: usize,
+    until_empty: bool,
+}
+
+impl<'a> Iterator for StrtabIter<'a> {
+    type Item = error::Result<(usize, &'a str)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset;
+        if offset >= self.strtab.len() {
+            return None
+        }
+        match self.strtab.read(offset) {
+            // nothing consumed means nothing more will be, e.g., for a zero `StrCtx::Length`
+            Ok((string, consumed)) if consumed == 0 || (self.until_empty && string.is_empty()) => {
+                self.offset = self.strtab.len();
+                None
+            },
+            Ok((string, consumed)) => {
+                self.offset = offset.saturating_add(consumed);
+                Some(Ok((offset, string)))
+            },
+            Err(err) => {
+                self.offset = self.strtab.len();
+                Some(Err(err))
+            },
+        }
+    }
+}
+
+/// A [Strtab](struct.Strtab.html) which remembers the strings it has looked up, for tables whose strings are looked up repeatedly, e.g., symbol names.
+/// Only present when `alloc` feature is used (the default).
+///
+/// # Example
+/// ```rust
+/// use scroll::Strtab;
+/// let strtab = Strtab::new(b"main\0_start\0").cached();
+/// assert_eq!(strtab.get(5).unwrap(), "_start");
+/// assert_eq!(strtab.get(5).unwrap(), "_start");
+/// assert_eq!(strtab.cached_count(), 1);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct CachedStrtab<'a> {
+    strtab: Strtab<'a>,
+    cache: RefCell<BTreeMap<usize, &'a str>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> CachedStrtab<'a> {
+    /// Gets the string at `offset` into the table, from the cache if it has been looked up before
+    pub fn get(&self, offset: usize) -> error::Result<&'a str> {
+        if let Some(&string) = self.cache.borrow().get(&offset) {
+            return Ok(string)
+        }
+        let string = self.strtab.get(offset)?;
+        self.cache.borrow_mut().insert(offset, string);
+        Ok(string)
+    }
+    /// The number of strings in the cache
+    pub fn cached_count(&self) -> usize {
+        self.cache.borrow().len()
+    }
+    /// The underlying table
+    pub fn strtab(&self) -> &Strtab<'a> {
+        &self.strtab
+    }
+    /// Drops the cache, and returns the underlying table
+    pub fn into_inner(self) -> Strtab<'a> {
+        self.strtab
+    }
+}
+
+/// A handle to a string added to a [StrtabBuilder](struct.StrtabBuilder.html), which is resolved to the string's offset once the table is built
+#[cfg(feature = "alloc")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StrtabHandle(usize);
+
+/// Builds a string table, e.g., an ELF `.strtab` or a Mach-O string table, for writing. Only present when `alloc` feature is used (the default).
+///
+/// Adding a string hands back a [StrtabHandle](struct.StrtabHandle.html); identical strings get the same handle, and so share their bytes.
+/// Since offsets are only known once every string has been added, [build](#method.build) lays the table out, and resolves each handle to its offset.
+///
+/// Strings are laid out in the order they were first added, each followed by the delimiter, `0` by default.
+/// With `share_suffixes`, a string which is the tail of another is not written at all, and points into it instead, e.g., `"bar"` into `"foobar\0"`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{StrtabBuilder, Strtab, Pwrite};
+/// let mut builder = StrtabBuilder::new().leading_delimiter(true).share_suffixes(true).align(4);
+/// let foobar = builder.add("foobar").unwrap();
+/// let bar = builder.add("bar").unwrap();
+/// let main = builder.add("main").unwrap();
+/// assert_eq!(builder.add("bar").unwrap(), bar);
+/// let table = builder.build().unwrap();
+/// assert_eq!(table.as_bytes(), b"\0foobar\0main\0\0\0\0");
+/// assert_eq!((table.offset(foobar), table.offset(bar), table.offset(main)), (1, 4, 8));
+/// // write it out after a header
+/// let mut bytes = [0xffu8; 20];
+/// bytes.pwrite(&table, 4).unwrap();
+/// let strtab = Strtab::parse(&bytes, 4, table.len(), table.ctx()).unwrap();
+/// assert_eq!(strtab.get(table.offset(bar)).unwrap(), "bar");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct StrtabBuilder {
+    delimiter: u8,
+    align: usize,
+    share_suffixes: bool,
+    leading_delimiter: bool,
+    // every distinct string, in the order they were added; a handle is an index into this
+    strings: Vec<String>,
+    handles: BTreeMap<String, StrtabHandle>,
+}
+
+#[cfg(feature = "alloc")]
+impl Default for StrtabBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl StrtabBuilder {
+    /// Creates a builder for a table of NUL terminated strings
+    pub fn new() -> Self {
+        Self::with_delimiter(0)
+    }
+    /// Creates a builder for a table of strings terminated by `delimiter`
+    pub fn with_delimiter(delimiter: u8) -> Self {
+        StrtabBuilder {
+            delimiter,
+            align: 1,
+            share_suffixes: false,
+            leading_delimiter: false,
+            strings: Vec::new(),
+            handles: BTreeMap::new(),
+        }
+    }
+    /// Pads the built table with the delimiter, up to a multiple of `align` bytes; a zero `align` is an error when the table is built
+    pub fn align(mut self, align: usize) -> Self {
+        self.align = align;
+        self
+    }
+    /// Whether strings which are suffixes of other strings point into them, rather than being written out themselves; off by default
+    pub fn share_suffixes(mut self, share_suffixes: bool) -> Self {
+        self.share_suffixes = share_suffixes;
+        self
+    }
+    /// Whether the table starts with a delimiter, so that offset 0 is the empty string, as ELF requires; off by default
+    pub fn leading_delimiter(mut self, leading_delimiter: bool) -> Self {
+        self.leading_delimiter = leading_delimiter;
+        self
+    }
+    /// Adds `string` to the table, unless it's already been added, and returns its handle
+    ///
+    /// Errors with `BadInput` over the offending byte if `string` contains the delimiter, since it couldn't be read back.
+    pub fn add(&mut self, string: &str) -> error::Result<StrtabHandle> {
+        if let Some(&handle) = self.handles.get(string) {
+            return Ok(handle)
+        }
+        if let Some(position) = string.bytes().position(|byte| byte == self.delimiter) {
+            return Err(error::Error::BadInput { range: position..position + 1, size: string.len(), msg: "string contains the delimiter" })
+        }
+        let handle = StrtabHandle(self.strings.len());
+        self.strings.push(String::from(string));
+        self.handles.insert(String::from(string), handle);
+        Ok(handle)
+    }
+    /// The number of distinct strings added
+    pub fn count(&self) -> usize {
+        self.strings.len()
+    }
+    /// For every string, the index of the string it's written as part of; itself, unless it's a suffix of another string and `share_suffixes` is on
+    fn owners(&self) -> Vec<usize> {
+        let mut owners: Vec<usize> = (0..self.strings.len()).collect();
+        if !self.share_suffixes {
+            return owners
+        }
+        // sorted by their reversed bytes, every string which ends with a given string comes right after it
+        let reversed = |index: usize| self.strings[index].bytes().rev();
+        let mut sorted = owners.clone();
+        sorted.sort_by(|&a, &b| reversed(a).cmp(reversed(b)));
+        for pair in (1..sorted.len()).rev() {
+            let (string, next) = (sorted[pair - 1], sorted[pair]);
+            if self.strings[next].ends_with(self.strings[string].as_str()) {
+                owners[string] = owners[next];
+            }
+        }
+        owners
+    }
+    /// Lays out the table, and resolves every handle to its offset
+    pub fn build(self) -> error::Result<StrtabBytes> {
+        let owners = self.owners();
+        let mut bytes = Vec::new();
+        if self.leading_delimiter {
+            bytes.push(self.delimiter);
+        }
+        // with a leading delimiter, the empty string is always at offset 0
+        let leading = |string: &str| self.leading_delimiter && string.is_empty();
+        let mut offsets = vec![0; self.strings.len()];
+        for (index, string) in self.strings.iter().enumerate() {
+            if owners[index] == index && !leading(string) {
+                offsets[index] = bytes.len();
+                bytes.extend_from_slice(string.as_bytes());
+                bytes.push(self.delimiter);
+            }
+        }
+        for (index, string) in self.strings.iter().enumerate() {
+            let owner = owners[index];
+            if owner != index && !leading(string) {
+                offsets[index] = offsets[owner] + self.strings[owner].len() - string.len();
+            }
+        }
+        let pad = padding(usize::MAX, bytes.len(), self.align)?;
+        bytes.resize(pad.end, self.delimiter);
+        Ok(StrtabBytes { bytes, offsets, delimiter: self.delimiter })
+    }
+}
+
+/// A string table laid out by a [StrtabBuilder](struct.StrtabBuilder.html), ready to be written with `pwrite` or `gwrite`, along with the offset of every string in it.
+/// Only present when `alloc` feature is used (the default).
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrtabBytes {
+    bytes: Vec<u8>,
+    offsets: Vec<usize>,
+    delimiter: u8,
+}
+
+#[cfg(feature = "alloc")]
+impl StrtabBytes {
+    /// The offset into the table of the string `handle` was returned for
+    ///
+    /// # Panics
+    /// If `handle` came from a different builder, and is out of range for this table.
+    pub fn offset(&self, handle: StrtabHandle) -> usize {
+        self.offsets[handle.0]
+    }
+    /// The size of the table in bytes, including any padding
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    /// The context to read the table's strings back with, e.g., in a [Strtab](struct.Strtab.html)
+    pub fn ctx(&self) -> StrCtx {
+        StrCtx::Delimiter(self.delimiter)
+    }
+    /// A [Strtab](struct.Strtab.html) reading the built table
+    pub fn strtab(&self) -> Strtab<'_> {
+        Strtab::with_ctx(&self.bytes, self.ctx())
+    }
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryIntoCtx<(usize, DefaultCtx)> for &StrtabBytes {
+    type Error = error::Error;
+    #[inline]
+    fn try_into_ctx(self, dst: &mut [u8], ctx: (usize, DefaultCtx)) -> error::Result<()> {
+        self.as_bytes().try_into_ctx(dst, ctx)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl TryWriteCtx<(usize, DefaultCtx)> for &StrtabBytes {
+    type Error = error::Error;
+    #[inline]
+    fn try_write_ctx(self, dst: &mut [u8], ctx: (usize, DefaultCtx)) -> error::Result<usize> {
+        self.as_bytes().try_write_ctx(dst, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Strtab, StrtabBuilder};
+    use super::super::{Error, Gwrite, VecWriter, LE};
+    use super::super::ctx::{StrCtx, LengthPrefix};
+
+    #[test]
+    fn get_and_iter() {
+        let strtab = Strtab::new(b"\0.text\0.data\0");
+        assert_eq!(strtab.get(0).unwrap(), "");
+        assert_eq!(strtab.get(1).unwrap(), ".text");
+        assert_eq!(strtab.get(10).unwrap(), "ta");
+        let entries: Vec<_> = strtab.iter().collect::<Result<_, _>>().unwrap();
+        assert_eq!(entries, [(0, ""), (1, ".text"), (7, ".data")]);
+        let mut count = 0;
+        for entry in &strtab {
+            let (offset, string) = entry.unwrap();
+            assert_eq!(strtab.get(offset).unwrap(), string);
+            count += 1;
+        }
+        assert_eq!(count, 3);
+        // unterminated strings run to the end of the table
+        assert_eq!(Strtab::new(b"abc").get(1).unwrap(), "bc");
+        assert!(Strtab::new(b"").iter().next().is_none());
+    }
+
+    #[test]
+    fn absolute_errors() {
+        let file = b"HEADER\0ok\0\xff\xfe\0";
+        let strtab = Strtab::parse(file, 7, 6, StrCtx::Delimiter(0)).unwrap();
+        assert_eq!(strtab.get(0).unwrap(), "ok");
+        match strtab.get(3) {
+            Err(Error::BadInput { range, .. }) => assert_eq!(range, 10..12),
+            res => panic!("expected a BadInput, got {:?}", res),
+        }
+        match strtab.get(6) {
+            Err(Error::BadOffset(13)) => (),
+            res => panic!("expected a BadOffset, got {:?}", res),
+        }
+        // iteration stops after the first error
+        let entries: Vec<_> = strtab.iter().collect();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[1].is_err());
+        assert!(Strtab::parse(file, 7, 7, StrCtx::Delimiter(0)).is_err());
+        match super::rebase(Error::Overflow { offset: 3, size: 2 }, 7) {
+            Error::Overflow { offset: 10, size: 2 } => (),
+            err => panic!("expected an Overflow at 10, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn multi() {
+        let block = Strtab::new(b"skip\0a=1\0b=2\0\0c=3\0");
+        let vars: Vec<_> = block.multi(5).collect::<Result<_, _>>().unwrap();
+        assert_eq!(vars, [(5, "a=1"), (9, "b=2")]);
+        // an empty list
+        assert_eq!(block.multi(13).count(), 0);
+        // running off the end of the table ends the list too
+        assert_eq!(block.multi(14).count(), 1);
+        let prefixed = Strtab::with_ctx(b"\x02hi\x03you\x00\x01!", StrCtx::Prefixed(LengthPrefix::U8, LE));
+        let words: Vec<_> = prefixed.multi(0).map(|word| word.unwrap().1).collect();
+        assert_eq!(words, ["hi", "you"]);
+        assert_eq!(prefixed.iter().count(), 4);
+    }
+
+    #[test]
+    fn cached() {
+        let strtab = Strtab::new(b"foo\0bar\0").cached();
+        assert_eq!(strtab.get(4).unwrap(), "bar");
+        assert_eq!(strtab.get(0).unwrap(), "foo");
+        assert_eq!(strtab.get(4).unwrap(), "bar");
+        assert_eq!(strtab.cached_count(), 2);
+        assert!(strtab.get(8).is_err());
+        assert_eq!(strtab.cached_count(), 2);
+        assert_eq!(strtab.into_inner().len(), 8);
+    }
+
+    #[test]
+    fn builder_dedupes_and_shares() {
+        let mut builder = StrtabBuilder::new();
+        let names = ["printf", "f", "printf", "intf", "", "puts"];
+        let handles: Vec<_> = names.iter().map(|name| builder.add(name).unwrap()).collect();
+        assert_eq!(handles[0], handles[2]);
+        assert_eq!(builder.count(), 5);
+        let plain = builder.clone().build().unwrap();
+        assert_eq!(plain.as_bytes(), b"printf\0f\0intf\0\0puts\0");
+        let shared = builder.share_suffixes(true).build().unwrap();
+        assert_eq!(shared.as_bytes(), b"printf\0puts\0");
+        for (name, &handle) in names.iter().zip(&handles) {
+            assert_eq!(plain.strtab().get(plain.offset(handle)).unwrap(), *name);
+            assert_eq!(shared.strtab().get(shared.offset(handle)).unwrap(), *name);
+        }
+        assert_eq!(shared.offset(handles[3]), 2);
+    }
+
+    #[test]
+    fn builder_layout() {
+        let mut builder = StrtabBuilder::with_delimiter(b'\n').leading_delimiter(true).align(8);
+        let empty = builder.add("").unwrap();
+        let a = builder.add("a").unwrap();
+        match builder.add("two\nlines") {
+            Err(Error::BadInput { range, size: 9, .. }) => assert_eq!(range, 3..4),
+            res => panic!("expected a BadInput, got {:?}", res),
+        }
+        let table = builder.build().unwrap();
+        assert_eq!(table.as_bytes(), b"\na\n\n\n\n\n\n");
+        assert_eq!((table.offset(empty), table.offset(a)), (0, 1));
+        let mut builder = StrtabBuilder::new().leading_delimiter(true).share_suffixes(true);
+        let (a, empty) = (builder.add("a").unwrap(), builder.add("").unwrap());
+        let shared = builder.build().unwrap();
+        assert_eq!(shared.as_bytes(), b"\0a\0");
+        assert_eq!((shared.offset(empty), shared.offset(a)), (0, 1));
+        // an empty table is still padded, but there's nothing to pad
+        assert!(StrtabBuilder::new().align(4).build().unwrap().is_empty());
+        assert!(StrtabBuilder::new().align(0).build().is_err());
+        let mut writer = VecWriter::new();
+        let offset = &mut 0;
+        writer.gwrite(&table, offset).unwrap();
+        writer.gwrite(&table, offset).unwrap();
+        assert_eq!((*offset, writer.len()), (16, 16));
+        assert!([0u8; 7].gwrite(&table, &mut 0).is_err());
+    }
+}
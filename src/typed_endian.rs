@@ -0,0 +1,223 @@
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+use ctx::{FromCtx, TryFromCtx, IntoCtx, TryIntoCtx, SizeWith};
+use endian::Endian;
+use error;
+use pod::Pod;
+
+macro_rules! endian_type {
+    ($name:ident, $typ:ty, $size:expr, $from:ident, $to:ident, $order:expr) => {
+        #[doc = concat!("A `", stringify!($typ), "` which is always stored in ", $order, " byte order, whatever the context's endianness")]
+        ///
+        /// It has an alignment of 1, so it can be a field of a `repr(C)` or `repr(packed)` record anywhere, and it is [Pod](trait.Pod.html),
+        /// so it can be borrowed in place with any context. Reading or writing it ignores the endianness of the context.
+        #[derive(Copy, Clone, Default)]
+        #[repr(transparent)]
+        pub struct $name([u8; $size]);
+
+        impl $name {
+            #[doc = concat!("Creates a new `", stringify!($name), "` holding `value`")]
+            #[inline]
+            pub fn new(value: $typ) -> Self {
+                $name(value.$to())
+            }
+            /// Decodes the value
+            #[inline]
+            pub fn get(self) -> $typ {
+                <$typ>::$from(self.0)
+            }
+            /// Replaces the value with `value`
+            #[inline]
+            pub fn set(&mut self, value: $typ) {
+                self.0 = value.$to();
+            }
+            #[doc = concat!("Wraps `bytes`, which are already in ", $order, " byte order")]
+            #[inline]
+            pub fn from_bytes(bytes: [u8; $size]) -> Self {
+                $name(bytes)
+            }
+            #[doc = concat!("Returns the value's bytes, in ", $order, " byte order")]
+            #[inline]
+            pub fn to_bytes(self) -> [u8; $size] {
+                self.0
+            }
+        }
+
+        impl From<$typ> for $name {
+            #[inline]
+            fn from(value: $typ) -> Self {
+                $name::new(value)
+            }
+        }
+
+        impl From<$name> for $typ {
+            #[inline]
+            fn from(value: $name) -> Self {
+                value.get()
+            }
+        }
+
+        impl PartialEq for $name {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                self.get() == other.get()
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                write!(fmt, "{}({:?})", stringify!($name), self.get())
+            }
+        }
+
+        unsafe impl Pod for $name {
+            const ENDIAN_AGNOSTIC: bool = true;
+        }
+
+        impl SizeWith<Endian> for $name {
+            type Units = usize;
+            #[inline]
+            fn size_with(_ctx: &Endian) -> usize {
+                $size
+            }
+        }
+
+        impl FromCtx<Endian> for $name {
+            #[inline]
+            fn from_ctx(src: &[u8], _ctx: Endian) -> Self {
+                let mut bytes = [0; $size];
+                bytes.copy_from_slice(&src[..$size]);
+                $name(bytes)
+            }
+        }
+
+        impl<'a> TryFromCtx<'a, (usize, Endian)> for $name {
+            type Error = error::Error;
+            #[inline]
+            fn try_from_ctx(src: &'a [u8], (offset, ctx): (usize, Endian)) -> error::Result<Self> {
+                let end = error::check_range(offset, $size, src.len())?;
+                Ok(FromCtx::from_ctx(&src[offset..end], ctx))
+            }
+        }
+
+        impl IntoCtx<Endian> for $name {
+            #[inline]
+            fn into_ctx(self, dst: &mut [u8], _ctx: Endian) {
+                dst[..$size].copy_from_slice(&self.0);
+            }
+        }
+
+        impl TryIntoCtx<(usize, Endian)> for $name {
+            type Error = error::Error;
+            #[inline]
+            fn try_into_ctx(self, dst: &mut [u8], (offset, ctx): (usize, Endian)) -> error::Result<()> {
+                let end = error::check_range(offset, $size, dst.len())?;
+                self.into_ctx(&mut dst[offset..end], ctx);
+                Ok(())
+            }
+        }
+    }
+}
+
+// the float wrappers only get `PartialEq`, like the floats themselves
+macro_rules! endian_int_type {
+    ($name:ident, $typ:ty, $size:expr, $from:ident, $to:ident, $order:expr) => {
+        endian_type!($name, $typ, $size, $from, $to, $order);
+
+        impl Eq for $name {}
+
+        impl Hash for $name {
+            #[inline]
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.get().hash(state)
+            }
+        }
+    }
+}
+
+endian_int_type!(U16Be, u16, 2, from_be_bytes, to_be_bytes, "big endian");
+endian_int_type!(U16Le, u16, 2, from_le_bytes, to_le_bytes, "little endian");
+endian_int_type!(I16Be, i16, 2, from_be_bytes, to_be_bytes, "big endian");
+endian_int_type!(I16Le, i16, 2, from_le_bytes, to_le_bytes, "little endian");
+endian_int_type!(U32Be, u32, 4, from_be_bytes, to_be_bytes, "big endian");
+endian_int_type!(U32Le, u32, 4, from_le_bytes, to_le_bytes, "little endian");
+endian_int_type!(I32Be, i32, 4, from_be_bytes, to_be_bytes, "big endian");
+endian_int_type!(I32Le, i32, 4, from_le_bytes, to_le_bytes, "little endian");
+endian_int_type!(U64Be, u64, 8, from_be_bytes, to_be_bytes, "big endian");
+endian_int_type!(U64Le, u64, 8, from_le_bytes, to_le_bytes, "little endian");
+endian_int_type!(I64Be, i64, 8, from_be_bytes, to_be_bytes, "big endian");
+endian_int_type!(I64Le, i64, 8, from_le_bytes, to_le_bytes, "little endian");
+endian_int_type!(U128Be, u128, 16, from_be_bytes, to_be_bytes, "big endian");
+endian_int_type!(U128Le, u128, 16, from_le_bytes, to_le_bytes, "little endian");
+endian_int_type!(I128Be, i128, 16, from_be_bytes, to_be_bytes, "big endian");
+endian_int_type!(I128Le, i128, 16, from_le_bytes, to_le_bytes, "little endian");
+endian_type!(F32Be, f32, 4, from_be_bytes, to_be_bytes, "big endian");
+endian_type!(F32Le, f32, 4, from_le_bytes, to_le_bytes, "little endian");
+endian_type!(F64Be, f64, 8, from_be_bytes, to_be_bytes, "big endian");
+endian_type!(F64Le, f64, 8, from_le_bytes, to_le_bytes, "little endian");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Pread, Pwrite, Gread, LE, BE, NATIVE};
+    use super::super::ctx::TryRefFromCtx;
+
+    #[test]
+    fn ignores_ctx() {
+        let bytes = [0x12, 0x34, 0x12, 0x34, 0, 0, 0, 1, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0];
+        for &ctx in &[LE, BE] {
+            let offset = &mut 0;
+            assert_eq!(bytes.gread_with::<U16Be>(offset, ctx).unwrap().get(), 0x1234);
+            assert_eq!(bytes.gread_with::<U16Le>(offset, ctx).unwrap().get(), 0x3412);
+            assert_eq!(bytes.gread_with::<I32Be>(offset, ctx).unwrap().get(), 1);
+            assert_eq!(bytes.gread_with::<F64Be>(offset, ctx).unwrap().get(), 1.0);
+            assert_eq!(*offset, 16);
+            let mut out = [0u8; 16];
+            out.pwrite_with(U16Be::new(0x1234), 0, ctx).unwrap();
+            out.pwrite_with(U16Le::new(0x3412), 2, ctx).unwrap();
+            out.pwrite_with(I32Be::from(1), 4, ctx).unwrap();
+            out.pwrite_with(F64Be::new(1.0), 8, ctx).unwrap();
+            assert_eq!(out, bytes);
+        }
+        assert!(bytes.pread::<U64Le>(12).is_err());
+        assert!([0u8; 3].pwrite(U32Le::new(1), 0).is_err());
+    }
+
+    #[test]
+    fn get_set_debug() {
+        let mut n = U32Le::default();
+        assert_eq!(n.get(), 0);
+        n.set(0xdeadbeef);
+        assert_eq!(n.to_bytes(), [0xef, 0xbe, 0xad, 0xde]);
+        assert_eq!(u32::from(n), 0xdeadbeef);
+        assert_eq!(U32Be::from_bytes([0xde, 0xad, 0xbe, 0xef]).get(), n.get());
+        assert_eq!(format!("{:?}", I16Be::new(-2)), "I16Be(-2)");
+        assert_eq!(format!("{:?}", F32Le::new(0.5)), "F32Le(0.5)");
+        assert_eq!(F64Le::new(0.0), F64Le::new(-0.0));
+        assert_eq!(::core::mem::align_of::<U128Be>(), 1);
+    }
+
+    #[test]
+    fn borrow_any_ctx() {
+        #[repr(C)]
+        #[derive(Copy, Clone)]
+        struct Header {
+            magic: U32Be,
+            length: U16Le,
+            kind: u8,
+            flags: u8,
+        }
+        unsafe impl Pod for Header {}
+        let bytes = [0xca, 0xfe, 0xba, 0xbe, 0x08, 0, 1, 2, 0, 0];
+        // alignment 1, so any offset can be borrowed
+        let header = bytes.pread_ref::<Header>(0).unwrap();
+        assert_eq!(header.magic.get(), 0xcafebabe);
+        assert_eq!(header.length.get(), 8);
+        assert_eq!((header.kind, header.flags), (1, 2));
+        let lengths = <[U16Le]>::try_ref_from_ctx(&bytes[..], (3, 2, !NATIVE)).unwrap();
+        assert_eq!(lengths, [U16Le::new(0x08be), U16Le::new(0x0100)]);
+        // but a record holding native integers still needs the native endianness
+        assert!(<[Header]>::try_ref_from_ctx(&bytes[..], (0, 1, !NATIVE)).is_err());
+    }
+}
@@ -0,0 +1,465 @@
+#[cfg(feature = "alloc")]
+use core::char;
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+use ctx::{self, LengthPrefix, TryIntoCtx, TryWriteCtx};
+#[cfg(feature = "alloc")]
+use ctx::{TryFromCtx, TryReadCtx};
+use endian::Endian;
+use error;
+use pwrite::Pwrite;
+
+/// The byte order mark, which a wide string may start with to say which byte order it's in
+const BOM: u32 = 0xfeff;
+
+/// A Unicode encoding with code units wider than a byte
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WideEncoding {
+    /// Two byte code units, with characters outside the basic multilingual plane encoded as a surrogate pair
+    Utf16,
+    /// Four byte code units, one per character
+    Utf32,
+}
+
+impl WideEncoding {
+    /// The size of a code unit in bytes
+    #[inline]
+    pub fn unit_size(self) -> usize {
+        match self {
+            WideEncoding::Utf16 => 2,
+            WideEncoding::Utf32 => 4,
+        }
+    }
+}
+
+/// How the extent of a wide string is determined; unlike `StrCtx`, every size here is counted in code units, not bytes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WideExtent {
+    /// The string ends at the first NUL code unit, or the end of the input; the NUL is written after the string
+    Nul,
+    /// The string is exactly this many code units long
+    Length(usize),
+    /// The string lives in a fixed width field of this many code units, and ends at the first NUL code unit;
+    /// the remainder of the field is filled with NULs when written
+    Padded(usize),
+    /// The string's length in code units immediately precedes it, encoded as a `LengthPrefix` in the string's byte order
+    Prefixed(LengthPrefix),
+}
+
+/// The parsing context for reading and writing UTF-16 or UTF-32 strings, e.g., in PE resources, Windows registry hives, or ID3v2 tags
+///
+/// Strings are read and written as a [Wide](struct.Wide.html), in the context's byte order.
+/// Any byte order mark counts towards the string's length, and `with_bom` controls it:
+///
+/// * reading, a leading BOM is skipped, and the string is decoded in the byte order it says instead of the context's
+/// * writing, a BOM is written first, in the context's byte order
+///
+/// Without it, a BOM is just a `U+FEFF` character. Lone surrogates, or UTF-32 code units which aren't characters, are a `BadInput` error over the
+/// offending code unit, unless the context is `lossy`, in which case they decode as `U+FFFD`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{Wide, WideCtx, WideExtent, Pread, Pwrite, LE};
+/// use scroll::ctx::LengthPrefix;
+/// // a PE resource directory string: a u16 count of UTF-16 code units, and no terminator
+/// let mut bytes = [0u8; 12];
+/// let ctx = WideCtx::utf16(WideExtent::Prefixed(LengthPrefix::U16), LE);
+/// bytes.pwrite_with(Wide("ICON"), 0, ctx).unwrap();
+/// assert_eq!(bytes[..10], [4, 0, b'I', 0, b'C', 0, b'O', 0, b'N', 0]);
+/// let name: Wide<String> = bytes.pread_with(0, ctx).unwrap();
+/// assert_eq!(name.0, "ICON");
+/// // a NUL terminated string with a big endian BOM, read with a little endian context
+/// let bytes = b"\xfe\xff\x00h\x00i\x00\x00";
+/// let ctx = WideCtx::utf16(WideExtent::Nul, LE).with_bom(true);
+/// assert_eq!(bytes.pread_with::<Wide<String>>(0, ctx).unwrap().0, "hi");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WideCtx {
+    encoding: WideEncoding,
+    extent: WideExtent,
+    endian: Endian,
+    bom: bool,
+    lossy: bool,
+}
+
+impl WideCtx {
+    /// A context for `encoding` strings with the given `extent`, in the byte order `endian`
+    pub fn new(encoding: WideEncoding, extent: WideExtent, endian: Endian) -> Self {
+        WideCtx { encoding, extent, endian, bom: false, lossy: false }
+    }
+    /// A context for UTF-16 strings with the given `extent`, in the byte order `endian`
+    pub fn utf16(extent: WideExtent, endian: Endian) -> Self {
+        Self::new(WideEncoding::Utf16, extent, endian)
+    }
+    /// A context for UTF-32 strings with the given `extent`, in the byte order `endian`
+    pub fn utf32(extent: WideExtent, endian: Endian) -> Self {
+        Self::new(WideEncoding::Utf32, extent, endian)
+    }
+    /// Whether strings are read and written with a byte order mark; off by default
+    pub fn with_bom(mut self, bom: bool) -> Self {
+        self.bom = bom;
+        self
+    }
+    /// Whether invalid code units decode as `U+FFFD`, rather than being an error; off by default
+    pub fn lossy(mut self, lossy: bool) -> Self {
+        self.lossy = lossy;
+        self
+    }
+    pub fn encoding(&self) -> WideEncoding {
+        self.encoding
+    }
+    pub fn extent(&self) -> WideExtent {
+        self.extent
+    }
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+}
+
+impl Default for WideCtx {
+    /// NUL terminated UTF-16, in the native byte order
+    #[inline]
+    fn default() -> Self {
+        WideCtx::utf16(WideExtent::Nul, ctx::CTX)
+    }
+}
+
+/// A string which is read and written as UTF-16 or UTF-32 with a [WideCtx](struct.WideCtx.html), rather than as UTF-8 with a `StrCtx`
+///
+/// Decode into a `Wide<String>`, and encode from a `Wide<&str>`, or a `Wide` of anything else which is `AsRef<str>`.
+/// # Example
+/// ```rust
+/// use scroll::{Wide, WideCtx, WideExtent, Gread, Gwrite, BE};
+/// let ctx = WideCtx::utf32(WideExtent::Nul, BE);
+/// let mut bytes = [0u8; 16];
+/// let offset = &mut 0;
+/// bytes.gwrite_with(Wide("\u{1f980}!"), offset, ctx).unwrap();
+/// assert_eq!(*offset, 12);
+/// let Wide(crab): Wide<String> = bytes.gread_with(&mut 0, ctx).unwrap();
+/// assert_eq!(crab, "\u{1f980}!");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Wide<S>(pub S);
+
+impl<S> Wide<S> {
+    /// Unwraps the string
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.0
+    }
+}
+
+/// Reads the code unit at `offset`, which must be in bounds
+#[inline]
+fn unit_at(src: &[u8], offset: usize, encoding: WideEncoding, endian: Endian) -> u32 {
+    match encoding {
+        WideEncoding::Utf16 => {
+            let bytes = [src[offset], src[offset + 1]];
+            u32::from(if endian.is_little() { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+        },
+        WideEncoding::Utf32 => {
+            let bytes = [src[offset], src[offset + 1], src[offset + 2], src[offset + 3]];
+            if endian.is_little() { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) }
+        },
+    }
+}
+
+/// The offset of the first NUL code unit in `range`, or its end
+fn nul_offset(src: &[u8], range: Range<usize>, encoding: WideEncoding) -> usize {
+    let unit = encoding.unit_size();
+    (range.start..range.end).step_by(unit)
+        .find(|&offset| src[offset..offset + unit].iter().all(|&byte| byte == 0))
+        .unwrap_or(range.end)
+}
+
+/// Finds the code units of the string at `offset` using `ctx`, and returns their range in `src` along with the number of bytes consumed, including any terminator, padding or length prefix
+fn wide_extent(src: &[u8], offset: usize, ctx: WideCtx) -> error::Result<(Range<usize>, usize)> {
+    let len = src.len();
+    let unit = ctx.encoding.unit_size();
+    let (start, count, consumed) = match ctx.extent {
+        WideExtent::Nul => {
+            if offset >= len {
+                return Err(error::Error::BadOffset(offset))
+            }
+            let whole = offset + (len - offset) / unit * unit;
+            let nul = nul_offset(src, offset..whole, ctx.encoding);
+            if nul == whole {
+                // without a terminator the string runs to the end of `src`, which must be a whole number of code units
+                error::check_range(offset, (len - offset).div_ceil(unit) * unit, len)?;
+            }
+            let count = nul - offset;
+            (offset, count, if nul < whole { count + unit } else { count })
+        },
+        WideExtent::Length(count) => {
+            let count = count.saturating_mul(unit);
+            (offset, count, count)
+        },
+        WideExtent::Padded(width) => {
+            let width = width.saturating_mul(unit);
+            let end = error::check_range(offset, width, len)?;
+            (offset, nul_offset(src, offset..end, ctx.encoding) - offset, width)
+        },
+        WideExtent::Prefixed(prefix) => {
+            let (count, size) = ctx::read_length_prefix(src, offset, prefix, ctx.endian)?;
+            let count = count.saturating_mul(unit);
+            (offset + size, count, size.saturating_add(count))
+        },
+    };
+    let end = error::check_range(start, count, len)?;
+    Ok((start..end, consumed))
+}
+
+/// Decodes the code units in `range` of `src`, pushing every character onto `string`
+#[cfg(feature = "alloc")]
+fn decode(src: &[u8], range: Range<usize>, ctx: WideCtx, string: &mut String) -> error::Result<()> {
+    let unit = ctx.encoding.unit_size();
+    let (mut offset, end) = (range.start, range.end);
+    let mut endian = ctx.endian;
+    if ctx.bom && offset < end {
+        if unit_at(src, offset, ctx.encoding, endian) == BOM {
+            offset += unit;
+        } else if unit_at(src, offset, ctx.encoding, !endian) == BOM {
+            endian = !endian;
+            offset += unit;
+        }
+    }
+    while offset < end {
+        let code = unit_at(src, offset, ctx.encoding, endian);
+        let (character, size, msg) = match ctx.encoding {
+            WideEncoding::Utf16 => match code {
+                0xd800..=0xdbff if offset + 2 * unit <= end => {
+                    let low = unit_at(src, offset + unit, ctx.encoding, endian);
+                    if (0xdc00..=0xdfff).contains(&low) {
+                        (char::from_u32(0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00)), 2 * unit, "")
+                    } else {
+                        (None, unit, "lone utf16 surrogate")
+                    }
+                },
+                _ => (char::from_u32(code), unit, "lone utf16 surrogate"),
+            },
+            WideEncoding::Utf32 => (char::from_u32(code), unit, "invalid utf32 code point"),
+        };
+        match character {
+            Some(character) => string.push(character),
+            None if ctx.lossy => string.push(char::REPLACEMENT_CHARACTER),
+            None => return Err(error::Error::BadInput { range: offset..offset + unit, size: end - range.start, msg }),
+        }
+        offset += size;
+    }
+    Ok(())
+}
+
+/// Reads a `String` from `src` using `ctx`, and returns it with the number of bytes consumed
+#[cfg(feature = "alloc")]
+fn read_wide(src: &[u8], offset: usize, ctx: WideCtx) -> error::Result<(String, usize)> {
+    let (range, consumed) = wide_extent(src, offset, ctx)?;
+    // don't trust the extent enough to preallocate it, it may be all surrogates
+    let mut string = String::new();
+    decode(src, range, ctx, &mut string)?;
+    Ok((string, consumed))
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TryFromCtx<'a, (usize, WideCtx)> for Wide<String> {
+    type Error = error::Error;
+    #[inline]
+    /// Decode a `String` from `src` using the encoding, extent and byte order of the `WideCtx`
+    fn try_from_ctx(src: &'a [u8], (offset, ctx): (usize, WideCtx)) -> error::Result<Self> {
+        read_wide(src, offset, ctx).map(|(string, _)| Wide(string))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> TryReadCtx<'a, (usize, WideCtx)> for Wide<String> {
+    type Error = error::Error;
+    #[inline]
+    fn try_read_ctx(src: &'a [u8], (offset, ctx): (usize, WideCtx)) -> error::Result<(Self, usize)> {
+        read_wide(src, offset, ctx).map(|(string, consumed)| (Wide(string), consumed))
+    }
+}
+
+/// The number of code units `string` encodes to with `ctx`, including any BOM
+fn unit_count(string: &str, ctx: WideCtx) -> usize {
+    let count = match ctx.encoding {
+        WideEncoding::Utf16 => string.encode_utf16().count(),
+        WideEncoding::Utf32 => string.chars().count(),
+    };
+    if ctx.bom { count + 1 } else { count }
+}
+
+/// Writes the code unit `code` into `dst` at `offset`, which must be in bounds
+#[inline]
+fn write_unit(dst: &mut [u8], offset: usize, code: u32, ctx: WideCtx) -> usize {
+    let unit = ctx.encoding.unit_size();
+    let dst = &mut dst[offset..offset + unit];
+    match (ctx.encoding, ctx.endian.is_little()) {
+        (WideEncoding::Utf16, true) => dst.copy_from_slice(&(code as u16).to_le_bytes()),
+        (WideEncoding::Utf16, false) => dst.copy_from_slice(&(code as u16).to_be_bytes()),
+        (WideEncoding::Utf32, true) => dst.copy_from_slice(&code.to_le_bytes()),
+        (WideEncoding::Utf32, false) => dst.copy_from_slice(&code.to_be_bytes()),
+    }
+    unit
+}
+
+/// Encodes `string` into `dst` at `offset` using `ctx`, i.e., including any BOM, terminator, padding or length prefix, and returns the number of bytes written
+fn write_wide(string: &str, dst: &mut [u8], offset: usize, ctx: WideCtx) -> error::Result<usize> {
+    let unit = ctx.encoding.unit_size();
+    let count = unit_count(string, ctx);
+    let bytes = count.saturating_mul(unit);
+    let (mut data, size) = match ctx.extent {
+        WideExtent::Nul => (offset, bytes.saturating_add(unit)),
+        WideExtent::Length(length) => {
+            if count != length {
+                return Err(error::Error::BadInput { range: offset..offset.saturating_add(bytes), size: length, msg: "string length does not match context length" })
+            }
+            (offset, bytes)
+        },
+        WideExtent::Padded(width) => {
+            if count > width {
+                return Err(error::Error::BadInput { range: offset..offset.saturating_add(bytes), size: width, msg: "string is wider than its padded field" })
+            }
+            (offset, width.saturating_mul(unit))
+        },
+        WideExtent::Prefixed(prefix) => {
+            let max = match prefix {
+                LengthPrefix::U8 => u8::MAX as u64,
+                LengthPrefix::U16 => u16::MAX as u64,
+                LengthPrefix::U32 => u32::MAX as u64,
+                LengthPrefix::Uleb128 => u64::MAX,
+            };
+            if count as u64 > max {
+                return Err(error::Error::BadInput { range: offset..offset.saturating_add(bytes), size: count, msg: "string is too long for its length prefix" })
+            }
+            let prefix_size = ctx::length_prefix_size(prefix, count);
+            (offset.saturating_add(prefix_size), prefix_size.saturating_add(bytes))
+        },
+    };
+    let end = error::check_range(offset, size, dst.len())?;
+    if let WideExtent::Prefixed(prefix) = ctx.extent {
+        match prefix {
+            LengthPrefix::U8 => dst.pwrite_with(count as u8, offset, ctx.endian)?,
+            LengthPrefix::U16 => dst.pwrite_with(count as u16, offset, ctx.endian)?,
+            LengthPrefix::U32 => dst.pwrite_with(count as u32, offset, ctx.endian)?,
+            LengthPrefix::Uleb128 => dst.pwrite(::leb128::Uleb128::new(count as u64), offset)?,
+        }
+    }
+    if ctx.bom {
+        data += write_unit(dst, data, BOM, ctx);
+    }
+    match ctx.encoding {
+        WideEncoding::Utf16 => for code in string.encode_utf16() {
+            data += write_unit(dst, data, u32::from(code), ctx);
+        },
+        WideEncoding::Utf32 => for character in string.chars() {
+            data += write_unit(dst, data, u32::from(character), ctx);
+        },
+    }
+    // the terminator, or the padding
+    dst[data..end].fill(0);
+    Ok(size)
+}
+
+impl<S: AsRef<str>> TryIntoCtx<(usize, WideCtx)> for Wide<S> {
+    type Error = error::Error;
+    #[inline]
+    /// Encode the string into `dst` at `offset` using the `WideCtx`, i.e., including any BOM, terminator, padding or length prefix
+    fn try_into_ctx(self, dst: &mut [u8], (offset, ctx): (usize, WideCtx)) -> error::Result<()> {
+        write_wide(self.0.as_ref(), dst, offset, ctx).map(|_| ())
+    }
+}
+
+impl<S: AsRef<str>> TryWriteCtx<(usize, WideCtx)> for Wide<S> {
+    type Error = error::Error;
+    #[inline]
+    fn try_write_ctx(self, dst: &mut [u8], (offset, ctx): (usize, WideCtx)) -> error::Result<usize> {
+        write_wide(self.0.as_ref(), dst, offset, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Wide, WideCtx, WideExtent};
+    use super::super::{Pread, Pwrite, Gread, Gwrite, Error, LE, BE};
+    use super::super::ctx::LengthPrefix;
+
+    #[test]
+    fn round_trips() {
+        let text = "h\u{e9}llo \u{1f980}";
+        let contexts = [
+            WideCtx::utf16(WideExtent::Nul, LE),
+            WideCtx::utf16(WideExtent::Length(8), BE),
+            WideCtx::utf16(WideExtent::Padded(12), LE).with_bom(true),
+            WideCtx::utf16(WideExtent::Prefixed(LengthPrefix::Uleb128), BE),
+            WideCtx::utf32(WideExtent::Nul, BE).with_bom(true),
+            WideCtx::utf32(WideExtent::Prefixed(LengthPrefix::U32), LE),
+        ];
+        let sizes = [18, 16, 24, 17, 36, 32];
+        for (&ctx, &size) in contexts.iter().zip(&sizes) {
+            let mut bytes = [0xffu8; 40];
+            let offset = &mut 1;
+            bytes.gwrite_with(Wide(text), offset, ctx).unwrap();
+            assert_eq!(*offset, 1 + size, "{:?}", ctx);
+            let offset = &mut 1;
+            assert_eq!(bytes.gread_with::<Wide<String>>(offset, ctx).unwrap().0, text);
+            assert_eq!(*offset, 1 + size);
+        }
+        let mut bytes = [0xffu8; 8];
+        bytes.pwrite_with(Wide("ab"), 0, WideCtx::utf16(WideExtent::Padded(4), BE)).unwrap();
+        assert_eq!(bytes, [0, b'a', 0, b'b', 0, 0, 0, 0]);
+        bytes.pwrite_with(Wide(&String::from("\u{1f980}")), 0, WideCtx::utf16(WideExtent::Nul, LE)).unwrap();
+        assert_eq!(bytes[..6], [0x3e, 0xd8, 0x80, 0xdd, 0, 0]);
+        assert!(bytes.pwrite_with(Wide("abcde"), 0, WideCtx::utf16(WideExtent::Padded(4), BE)).is_err());
+        assert!(bytes.pwrite_with(Wide("ab"), 0, WideCtx::utf16(WideExtent::Length(3), BE)).is_err());
+        assert!(bytes.pwrite_with(Wide("abcd"), 0, WideCtx::utf16(WideExtent::Nul, BE)).is_err());
+    }
+
+    #[test]
+    fn bom() {
+        let ctx = WideCtx::utf16(WideExtent::Nul, BE).with_bom(true);
+        assert_eq!(b"\xff\xfeo\0k\0\0\0".pread_with::<Wide<String>>(0, ctx).unwrap().0, "ok");
+        assert_eq!(b"\xfe\xff\0o\0k\0\0".pread_with::<Wide<String>>(0, ctx).unwrap().0, "ok");
+        assert_eq!(b"\0o\0k".pread_with::<Wide<String>>(0, ctx).unwrap().0, "ok");
+        // without detection, it's just a character
+        let ctx = WideCtx::utf16(WideExtent::Nul, BE);
+        assert_eq!(b"\xfe\xff\0o\0\0".pread_with::<Wide<String>>(0, ctx).unwrap().0, "\u{feff}o");
+        let ctx = WideCtx::utf32(WideExtent::Length(2), BE).with_bom(true);
+        assert_eq!(b"\xff\xfe\0\0!\0\0\0".pread_with::<Wide<String>>(0, ctx).unwrap().0, "!");
+        let mut bytes = [0u8; 8];
+        bytes.pwrite_with(Wide("!"), 0, ctx).unwrap();
+        assert_eq!(bytes, *b"\0\0\xfe\xff\0\0\0!");
+    }
+
+    #[test]
+    fn invalid() {
+        // "a", a lone high surrogate, "b", a lone low surrogate
+        let bytes = [0x61, 0, 0x3d, 0xd8, 0x62, 0, 0x00, 0xdc, 0, 0];
+        let ctx = WideCtx::utf16(WideExtent::Nul, LE);
+        match bytes.pread_with::<Wide<String>>(0, ctx) {
+            Err(Error::BadInput { range, .. }) => assert_eq!(range, 2..4),
+            res => panic!("expected a BadInput, got {:?}", res),
+        }
+        match bytes.pread_with::<Wide<String>>(4, ctx) {
+            Err(Error::BadInput { range, .. }) => assert_eq!(range, 6..8),
+            res => panic!("expected a BadInput, got {:?}", res),
+        }
+        // a high surrogate at the very end of the string
+        match bytes.pread_with::<Wide<String>>(0, WideCtx::utf16(WideExtent::Length(2), LE)) {
+            Err(Error::BadInput { range, .. }) => assert_eq!(range, 2..4),
+            res => panic!("expected a BadInput, got {:?}", res),
+        }
+        assert_eq!(bytes.pread_with::<Wide<String>>(0, ctx.lossy(true)).unwrap().0, "a\u{fffd}b\u{fffd}");
+        let ctx = WideCtx::utf32(WideExtent::Length(2), BE);
+        match b"\0\0\0a\0\x11\0\0".pread_with::<Wide<String>>(0, ctx) {
+            Err(Error::BadInput { range, .. }) => assert_eq!(range, 4..8),
+            res => panic!("expected a BadInput, got {:?}", res),
+        }
+        // extents out of bounds, or not a whole number of code units
+        assert!(b"a\0b".pread_with::<Wide<String>>(0, WideCtx::utf16(WideExtent::Nul, LE)).is_err());
+        assert!(b"a\0".pread_with::<Wide<String>>(2, WideCtx::utf16(WideExtent::Nul, LE)).is_err());
+        assert!(b"\x02a\0".pread_with::<Wide<String>>(0, WideCtx::utf16(WideExtent::Prefixed(LengthPrefix::U8), LE)).is_err());
+        assert!(b"a\0".pread_with::<Wide<String>>(0, WideCtx::utf16(WideExtent::Length(usize::max_value()), LE)).is_err());
+    }
+}
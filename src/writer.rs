@@ -0,0 +1,223 @@
+use core::fmt::Debug;
+use core::ops::Deref;
+
+use alloc::vec::Vec;
+
+use ctx::{TryIntoCtx, TryWriteCtx};
+use error;
+use pwrite::Pwrite;
+use align::padding;
+
+/// A growable, zero-filled byte vector for `pwrite`/`gwrite`-ing values whose final size isn't known up front
+///
+/// Writes which land past the end of the vector extend it, filling any gap with zeroes, so serializers don't need to pre-size a buffer or make two passes.
+/// Writes at arbitrary offsets still work, so a header can be backpatched once the data after it has been written. Only present when `alloc` feature is used (the default).
+///
+/// **NB**: `pwrite` and `gwrite` may need to retry a write after growing, and so require `N: Clone`; all the builtin types are `Copy`.
+///
+/// # Example
+/// ```rust
+/// use scroll::{VecWriter, Pread, BE};
+/// let mut writer = VecWriter::new();
+/// let offset = &mut 4;
+/// writer.gwrite_with(0xdeadbeefu32, offset, BE).unwrap();
+/// writer.gwrite_with(0x7fu8, offset, BE).unwrap();
+/// // backpatch the size of the payload at the start
+/// writer.pwrite_with((*offset - 4) as u32, 0, BE).unwrap();
+/// assert_eq!(writer.pread_with::<u32>(0, BE).unwrap(), 5);
+/// writer.pwrite("hello", 12).unwrap();
+/// assert_eq!(writer.len(), 18);
+/// assert_eq!(&writer.into_inner()[4..], b"\xde\xad\xbe\xef\x7f\0\0\0hello\0");
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct VecWriter {
+    inner: Vec<u8>,
+}
+
+impl VecWriter {
+    /// Creates a new, empty writer
+    pub fn new() -> Self {
+        VecWriter { inner: Vec::new() }
+    }
+    /// Creates a new, empty writer with room for `capacity` bytes before reallocating
+    pub fn with_capacity(capacity: usize) -> Self {
+        VecWriter { inner: Vec::with_capacity(capacity) }
+    }
+    /// Consumes self and returns the inner byte vector
+    pub fn into_inner(self) -> Vec<u8> {
+        self.inner
+    }
+    pub fn as_slice(&self) -> &[u8] {
+        self.inner.as_slice()
+    }
+    /// Zero-fills the vector so that it is at least `len` bytes long
+    #[inline]
+    pub fn reserve_to(&mut self, len: usize) {
+        if len > self.inner.len() {
+            self.inner.resize(len, 0);
+        }
+    }
+    /// Write `n` at `offset` with a default `Ctx`, growing the vector as needed
+    #[inline]
+    pub fn pwrite<Ctx, N>(&mut self, n: N, offset: usize) -> error::Result<()>
+        where Ctx: Copy + Default + Debug,
+              N: TryIntoCtx<(usize, Ctx), Error = error::Error> + Clone {
+        self.pwrite_with(n, offset, Ctx::default())
+    }
+    /// Write `n` at `offset` with `ctx`, growing the vector as needed
+    pub fn pwrite_with<Ctx, N>(&mut self, n: N, offset: usize, ctx: Ctx) -> error::Result<()>
+        where Ctx: Copy + Default + Debug,
+              N: TryIntoCtx<(usize, Ctx), Error = error::Error> + Clone {
+        self.retry_growing(|dst| dst.pwrite_with(n.clone(), offset, ctx))
+    }
+    /// Calls `write` on the vector, growing it to the end of the range it needed and trying again for as long as the write fails for lack of room
+    ///
+    /// If the write still fails, the vector is shrunk back to its original length.
+    fn retry_growing<T, F: FnMut(&mut [u8]) -> error::Result<T>>(&mut self, mut write: F) -> error::Result<T> {
+        let len = self.inner.len();
+        loop {
+            match write(self.inner.as_mut_slice()) {
+                // only retry if growing actually gives the write more room, otherwise the error is genuine
+                Err(error::Error::BadRange { ref range, size }) if size == self.inner.len() && range.end > size => {
+                    let end = range.end;
+                    self.reserve_to(end);
+                }
+                Err(err) => {
+                    self.inner.truncate(len);
+                    return Err(err)
+                }
+                res => return res,
+            }
+        }
+    }
+    /// Write `n` at `offset` with a default `Ctx`, growing the vector as needed, and increment the offset by however many bytes `n` wrote
+    #[inline]
+    pub fn gwrite<Ctx, N>(&mut self, n: N, offset: &mut usize) -> error::Result<()>
+        where Ctx: Copy + Default + Debug,
+              N: TryWriteCtx<(usize, Ctx), Error = error::Error> + Clone {
+        self.gwrite_with(n, offset, Ctx::default())
+    }
+    /// Write `n` at `offset` with `ctx`, growing the vector as needed, and increment the offset by however many bytes `n` wrote
+    pub fn gwrite_with<Ctx, N>(&mut self, n: N, offset: &mut usize, ctx: Ctx) -> error::Result<()>
+        where Ctx: Copy + Default + Debug,
+              N: TryWriteCtx<(usize, Ctx), Error = error::Error> + Clone {
+        let o = *offset;
+        let size = self.retry_growing(|dst| n.clone().try_write_ctx(dst, (o, ctx)))?;
+        *offset = error::checked_offset(o, size)?;
+        Ok(())
+    }
+    /// Pads the vector with `fill` from `offset` up to the next multiple of `align`, growing it as needed, and updates the offset
+    pub fn gwrite_align(&mut self, offset: &mut usize, align: usize, fill: u8) -> error::Result<()> {
+        // the vector grows, so only overflow limits the padding
+        let range = padding(usize::MAX, *offset, align)?;
+        self.reserve_to(range.end);
+        self.inner[range.clone()].fill(fill);
+        *offset = range.end;
+        Ok(())
+    }
+}
+
+impl From<Vec<u8>> for VecWriter {
+    fn from(inner: Vec<u8>) -> Self {
+        VecWriter { inner }
+    }
+}
+
+// this gets us Pread and Gread; `Pwrite` is deliberately not implemented via `AsMut` so the growing writes above are used
+impl AsRef<[u8]> for VecWriter {
+    fn as_ref(&self) -> &[u8] {
+        self.inner.as_slice()
+    }
+}
+
+impl Deref for VecWriter {
+    type Target = [u8];
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VecWriter;
+    use super::super::{ctx, Pread, Uleb128, LE, BE};
+
+    #[test]
+    fn pwrite_grows() {
+        let mut writer = VecWriter::new();
+        writer.pwrite_with(0xbeefu16, 2, LE).unwrap();
+        assert_eq!(writer.as_slice(), &[0, 0, 0xef, 0xbe]);
+        writer.pwrite_with(0xdeadu16, 0, BE).unwrap();
+        assert_eq!(writer.as_slice(), &[0xde, 0xad, 0xef, 0xbe]);
+        writer.pwrite("hi", 6).unwrap();
+        assert_eq!(writer.as_slice(), &[0xde, 0xad, 0xef, 0xbe, 0, 0, b'h', b'i', 0]);
+        writer.pwrite(Uleb128::new(624485), 9).unwrap();
+        assert_eq!(writer.len(), 12);
+        assert_eq!(writer.pread::<Uleb128>(9).unwrap(), Uleb128::new(624485));
+        writer.pwrite(&b"bytes"[..], 1).unwrap();
+        assert_eq!(writer.len(), 12);
+        assert_eq!(writer.pread_with::<&str>(1, ctx::NULL).unwrap(), "byteshi");
+    }
+
+    #[test]
+    fn pwrite_genuine_errors() {
+        use super::super::ctx::StrCtx;
+        let mut writer = VecWriter::new();
+        // too long for the context; growing won't help
+        assert!(writer.pwrite_with("hello", 0, StrCtx::Length(2)).is_err());
+        assert!(writer.pwrite_with("hello", 0, StrCtx::Padded(4, 0)).is_err());
+        // and failed writes leave the vector as it was
+        assert!(writer.pwrite_with("hello", 100, StrCtx::Length(2)).is_err());
+        assert!(writer.pwrite_with("hi", 100, StrCtx::Padded(4, 0)).is_ok());
+        assert!(writer.pwrite_with("hello", 200, StrCtx::Padded(4, 0)).is_err());
+        assert_eq!(writer.len(), 104);
+        assert!(writer.gwrite_with("hello", &mut 300, StrCtx::Length(2)).is_err());
+        assert_eq!(writer.len(), 104);
+    }
+
+    #[test]
+    fn gwrite_grows() {
+        let mut writer = VecWriter::from(vec![0xff]);
+        let offset = &mut 1;
+        writer.gwrite_with(1u32, offset, BE).unwrap();
+        writer.gwrite(2u64, offset).unwrap();
+        writer.gwrite_with(-1i8, offset, LE).unwrap();
+        assert_eq!(*offset, 14);
+        assert_eq!(writer.len(), 14);
+        let offset = &mut 1;
+        assert_eq!(writer.pread_with::<u32>(*offset, BE).unwrap(), 1);
+        *offset += 4;
+        assert_eq!(writer.pread::<u64>(*offset).unwrap(), 2);
+        assert_eq!(writer[0], 0xff);
+        assert_eq!(writer[13], 0xff);
+    }
+
+    #[test]
+    fn gwrite_variable_length() {
+        use super::super::ctx::StrCtx;
+        let mut writer = VecWriter::new();
+        let offset = &mut 0;
+        writer.gwrite(Uleb128::new(624485), offset).unwrap();
+        writer.gwrite_with("hello", offset, StrCtx::Delimiter(0)).unwrap();
+        writer.gwrite(&b"xyz"[..], offset).unwrap();
+        writer.gwrite_with(7u16, offset, BE).unwrap();
+        assert_eq!(*offset, 14);
+        assert_eq!(writer.len(), 14);
+        assert_eq!(&writer[3..], b"hello\0xyz\0\x07");
+    }
+
+    #[test]
+    fn gwrite_align_grows() {
+        let mut writer = VecWriter::new();
+        let offset = &mut 0;
+        writer.gwrite_with(1u8, offset, LE).unwrap();
+        writer.gwrite_align(offset, 4, 0xaa).unwrap();
+        assert_eq!(*offset, 4);
+        writer.gwrite_align(offset, 4, 0xaa).unwrap();
+        assert_eq!(writer.as_slice(), &[1, 0xaa, 0xaa, 0xaa]);
+        assert!(writer.gwrite_align(offset, 0, 0).is_err());
+        assert!(writer.gwrite_align(&mut (usize::MAX - 1), 4, 0).is_err());
+        assert_eq!(writer.len(), 4);
+    }
+}
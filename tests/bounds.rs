@@ -0,0 +1,223 @@
+// edge cases at the end of the input and at the end of the address space; every one of these must be an error, never a panic or a wrap
+
+extern crate scroll;
+
+use scroll::{Error, Pread, Pwrite, Gread, Gwrite, Uleb128, Sleb128, LE, BE};
+use scroll::ctx::{StrCtx, LengthPrefix};
+
+const MAX: usize = usize::MAX;
+
+fn is_bad_range<T: ::std::fmt::Debug>(res: scroll::Result<T>, start: usize, end: usize, len: usize) {
+    match res {
+        Err(Error::BadRange { ref range, size }) if range.start == start && range.end == end && size == len => (),
+        res => panic!("expected BadRange [{}..{}) of {}, got {:?}", start, end, len, res),
+    }
+}
+
+fn is_overflow<T: ::std::fmt::Debug>(res: scroll::Result<T>, offset: usize) {
+    match res {
+        Err(Error::Overflow { offset: o, .. }) if o == offset => (),
+        res => panic!("expected Overflow at {:#x}, got {:?}", offset, res),
+    }
+}
+
+macro_rules! primitive_bounds {
+    ($name:ident, $typ:ty, $value:expr) => {
+        #[test]
+        fn $name() {
+            const SIZE: usize = ::std::mem::size_of::<$typ>();
+            let value: $typ = $value;
+            for len in 0..(SIZE * 2 + 1) {
+                let mut bytes = vec![0u8; len];
+                if len >= SIZE {
+                    // the last offset it fits at
+                    let last = len - SIZE;
+                    bytes.pwrite_with(value, last, BE).unwrap();
+                    assert_eq!(bytes.pread_with::<$typ>(last, BE).unwrap(), value);
+                    let offset = &mut last.clone();
+                    assert_eq!(bytes.gread_with::<$typ>(offset, BE).unwrap(), value);
+                    assert_eq!(*offset, len);
+                    let offset = &mut last.clone();
+                    bytes.gwrite_with(value, offset, LE).unwrap();
+                    assert_eq!(*offset, len);
+                }
+                // one past it, and at the very end
+                for offset in [len.saturating_sub(SIZE) + 1, len].iter().cloned() {
+                    if offset + SIZE <= len {
+                        continue
+                    }
+                    is_bad_range(bytes.pread_with::<$typ>(offset, LE), offset, offset + SIZE, len);
+                    is_bad_range(bytes.pwrite_with(value, offset, LE), offset, offset + SIZE, len);
+                    let o = &mut offset.clone();
+                    is_bad_range(bytes.gread_with::<$typ>(o, LE), offset, offset + SIZE, len);
+                    assert_eq!(*o, offset);
+                    is_bad_range(bytes.gwrite_with(value, o, LE), offset, offset + SIZE, len);
+                    assert_eq!(*o, offset);
+                }
+                // the end of the read wraps around the address space
+                for offset in [MAX, MAX - SIZE + 1].iter().cloned() {
+                    is_overflow(bytes.pread_with::<$typ>(offset, LE), offset);
+                    is_overflow(bytes.pwrite_with(value, offset, LE), offset);
+                    let o = &mut offset.clone();
+                    is_overflow(bytes.gread_with::<$typ>(o, LE), offset);
+                    assert_eq!(*o, offset);
+                    is_overflow(bytes.gwrite_with(value, o, LE), offset);
+                    assert_eq!(*o, offset);
+                }
+                // the end is exactly `usize::MAX`, which doesn't wrap but is still out of bounds
+                is_bad_range(bytes.pread_with::<$typ>(MAX - SIZE, LE), MAX - SIZE, MAX, len);
+                is_bad_range(bytes.pwrite_with(value, MAX - SIZE, LE), MAX - SIZE, MAX, len);
+            }
+        }
+    }
+}
+
+primitive_bounds!(bounds_u8, u8, 0xfe);
+primitive_bounds!(bounds_i8, i8, -2);
+primitive_bounds!(bounds_u16, u16, 0xfeed);
+primitive_bounds!(bounds_i16, i16, -0x1234);
+primitive_bounds!(bounds_u32, u32, 0xdeadbeef);
+primitive_bounds!(bounds_i32, i32, -0x1234_5678);
+primitive_bounds!(bounds_u64, u64, 0xdead_beef_cafe_babe);
+primitive_bounds!(bounds_i64, i64, -0x1234_5678_9abc);
+primitive_bounds!(bounds_u128, u128, 0xdead_beef_cafe_babe_0123_4567_89ab_cdef);
+primitive_bounds!(bounds_i128, i128, -0x1234_5678_9abc_def0_1234);
+primitive_bounds!(bounds_f32, f32, 1.5);
+primitive_bounds!(bounds_f64, f64, -2.25);
+primitive_bounds!(bounds_usize, usize, 0x1234);
+
+#[test]
+fn bounds_composites() {
+    // arrays and tuples are bounds checked element by element, so the error is for the first element that doesn't fit
+    let mut bytes = [0u8; 8];
+    bytes.pwrite_with([1u16, 2, 3], 2, LE).unwrap();
+    assert_eq!(bytes.pread_with::<[u16; 3]>(2, LE).unwrap(), [1, 2, 3]);
+    is_bad_range(bytes.pread_with::<[u16; 3]>(3, LE), 7, 9, 8);
+    is_bad_range(bytes.pwrite_with([1u16, 2, 3], 3, LE), 7, 9, 8);
+    is_overflow(bytes.pread_with::<[u16; 3]>(MAX, LE), MAX);
+    is_overflow(bytes.pwrite_with([1u16, 2, 3], MAX, LE), MAX);
+    bytes.pwrite_with((1u8, 2u32), 3, BE).unwrap();
+    assert_eq!(bytes.pread_with::<(u8, u32)>(3, BE).unwrap(), (1, 2));
+    is_bad_range(bytes.pread_with::<(u8, u32)>(4, BE), 5, 9, 8);
+    is_overflow(bytes.pread_with::<(u8, u32)>(MAX, BE), MAX);
+    is_bad_range(bytes.pread_with::<(u8, u32)>(MAX - 1, BE), MAX - 1, MAX, 8);
+    // but `gread` checks the whole size up front, and leaves the offset alone
+    let offset = &mut 3;
+    is_bad_range(bytes.gread_with::<[u16; 3]>(offset, LE), 3, 9, 8);
+    assert_eq!(*offset, 3);
+    let offset = &mut (MAX - 4);
+    is_overflow(bytes.gread_with::<(u8, u32)>(offset, LE), MAX - 4);
+    assert_eq!(*offset, MAX - 4);
+}
+
+#[test]
+fn bounds_slices() {
+    let bytes = [b'h', b'i', 0, 0xff];
+    assert_eq!(bytes.pread_slice::<str>(0, 2).unwrap(), "hi");
+    assert_eq!(bytes.pread_slice::<[u8]>(4, 0).unwrap(), &[]);
+    is_bad_range(bytes.pread_slice::<[u8]>(4, 1), 4, 5, 4);
+    is_bad_range(bytes.pread_slice::<[u8]>(0, 5), 0, 5, 4);
+    is_overflow(bytes.pread_slice::<[u8]>(MAX, 1), MAX);
+    is_overflow(bytes.pread_slice::<str>(1, MAX), 1);
+    is_bad_range(bytes.pread_slice::<str>(0, MAX), 0, MAX, 4);
+    let offset = &mut 2;
+    is_overflow(bytes.gread_slice::<[u8]>(offset, MAX), 2);
+    assert_eq!(*offset, 2);
+    let mut bytes = [0u8; 4];
+    bytes.pwrite(&b"abcd"[..], 0).unwrap();
+    is_bad_range(bytes.pwrite(&b"ab"[..], 3), 3, 5, 4);
+    is_overflow(bytes.pwrite(&b"ab"[..], MAX), MAX);
+}
+
+#[test]
+fn bounds_strings() {
+    let bytes = *b"hello\0";
+    // delimited
+    assert_eq!(bytes.pread::<&str>(0).unwrap(), "hello");
+    assert_eq!(bytes.pread::<&str>(5).unwrap(), "");
+    match bytes.pread::<&str>(6) { Err(Error::BadOffset(6)) => (), res => panic!("{:?}", res) }
+    match bytes.pread::<&str>(MAX) { Err(Error::BadOffset(MAX)) => (), res => panic!("{:?}", res) }
+    // fixed length
+    assert_eq!(bytes.pread_with::<&str>(1, StrCtx::Length(4)).unwrap(), "ello");
+    is_bad_range(bytes.pread_with::<&str>(1, StrCtx::Length(6)), 1, 7, 6);
+    is_bad_range(bytes.pread_with::<&str>(0, StrCtx::Length(MAX)), 0, MAX, 6);
+    is_overflow(bytes.pread_with::<&str>(1, StrCtx::Length(MAX)), 1);
+    is_overflow(bytes.pread_with::<&str>(MAX, StrCtx::Length(1)), MAX);
+    // padded
+    assert_eq!(bytes.pread_with::<&str>(0, StrCtx::Padded(6, 0)).unwrap(), "hello");
+    is_bad_range(bytes.pread_with::<&str>(1, StrCtx::Padded(6, 0)), 1, 7, 6);
+    is_overflow(bytes.pread_with::<&str>(MAX, StrCtx::Padded(2, 0)), MAX);
+    is_overflow(bytes.pread_with::<&str>(2, StrCtx::Padded(MAX, 0)), 2);
+    // length prefixed, where the length is hostile
+    let prefixed = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    let uleb = StrCtx::Prefixed(LengthPrefix::Uleb128, LE);
+    is_overflow(prefixed.pread_with::<&str>(0, uleb), 10);
+    is_bad_range(prefixed.pread_with::<&str>(0, StrCtx::Prefixed(LengthPrefix::U32, LE)), 4, 4 + 0xffff_ffff, 10);
+    is_bad_range(prefixed.pread_with::<&str>(9, StrCtx::Prefixed(LengthPrefix::U16, LE)), 9, 11, 10);
+    is_overflow(prefixed.pread_with::<&str>(MAX, StrCtx::Prefixed(LengthPrefix::U8, LE)), MAX);
+    // owned strings and tables of strings share the same checks
+    is_overflow(bytes.pread_with::<String>(1, StrCtx::Length(MAX)), 1);
+    is_overflow(bytes.pread_vec_with::<&str>(MAX, 1, StrCtx::Length(1)), MAX);
+    is_overflow(bytes.pread_vec_with::<u32>(1, MAX, LE), 1);
+    is_bad_range(bytes.pread_vec_with::<u8>(0, 7, LE), 0, 7, 6);
+    // writes
+    let mut dst = [0xffu8; 6];
+    dst.pwrite("hello", 0).unwrap();
+    assert_eq!(&dst, b"hello\0");
+    is_bad_range(dst.pwrite("hello", 1), 1, 7, 6);
+    is_overflow(dst.pwrite("hi", MAX), MAX);
+    is_overflow(dst.pwrite("hi", MAX - 2), MAX - 2);
+    is_overflow(dst.pwrite_with("hi", MAX, StrCtx::Length(2)), MAX);
+    is_overflow(dst.pwrite_with("hi", MAX, StrCtx::Padded(4, 0)), MAX);
+    is_bad_range(dst.pwrite_with("hi", 3, StrCtx::Padded(4, 0)), 3, 7, 6);
+    is_overflow(dst.pwrite_with("hi", MAX, StrCtx::Prefixed(LengthPrefix::U16, BE)), MAX);
+    is_bad_range(dst.pwrite_with("hello", 0, StrCtx::Prefixed(LengthPrefix::U16, BE)), 0, 7, 6);
+}
+
+#[test]
+fn bounds_leb128() {
+    // a full width u64 takes 10 bytes
+    let max = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    assert_eq!(u64::from(max.pread::<Uleb128>(0).unwrap()), u64::MAX);
+    let min = [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f];
+    assert_eq!(i64::from(min.pread::<Sleb128>(0).unwrap()), i64::MIN);
+    // the continuation bit runs off the end
+    for len in 0..max.len() {
+        is_bad_range(max[..len].pread::<Uleb128>(0), len, len + 1, len);
+        is_bad_range(max[..len].pread::<Sleb128>(0), len, len + 1, len);
+        let offset = &mut 0;
+        assert!(Uleb128::read(&&max[..len], offset).is_err());
+        assert_eq!(*offset, 0);
+    }
+    // too many bytes, or too many bits in the last one
+    let long = [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00];
+    match long.pread::<Uleb128>(0) { Err(Error::BadInput { .. }) => (), res => panic!("{:?}", res) }
+    match long.pread::<Sleb128>(0) { Err(Error::BadInput { .. }) => (), res => panic!("{:?}", res) }
+    let wide = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+    match wide.pread::<Uleb128>(0) { Err(Error::BadInput { .. }) => (), res => panic!("{:?}", res) }
+    // at the end of the address space
+    is_overflow(max.pread::<Uleb128>(MAX), MAX);
+    is_overflow(max.pread::<Sleb128>(MAX), MAX);
+    is_bad_range(max.pread::<Uleb128>(MAX - 1), MAX - 1, MAX, 10);
+    // writes
+    let mut dst = [0u8; 10];
+    dst.pwrite(Uleb128::new(u64::MAX), 0).unwrap();
+    assert_eq!(dst, max);
+    is_bad_range(dst.pwrite(Uleb128::new(u64::MAX), 1), 1, 11, 10);
+    is_bad_range(dst.pwrite(Sleb128::new(i64::MIN), 1), 1, 11, 10);
+    is_overflow(dst.pwrite(Uleb128::new(0x80), MAX), MAX);
+    is_overflow(dst.pwrite(Sleb128::new(-1), MAX), MAX);
+    is_overflow(dst.pwrite(Uleb128::new(0x80), MAX - 1), MAX - 1);
+    let offset = &mut (MAX - 1);
+    assert!(Uleb128::write(&mut dst, offset, 0x80).is_err());
+    assert_eq!(*offset, MAX - 1);
+}
+
+#[test]
+fn bounds_context_display() {
+    let bytes = [0u8; 4];
+    let err = bytes.pread_with::<u32>(MAX - 1, LE).unwrap_err();
+    assert_eq!(err.to_string(), format!("offset {} plus size 4 overflows", MAX - 1));
+    let err: scroll::ContextError = err.into();
+    assert_eq!(err.to_string(), format!("offset {:#x} plus size 0x4 overflows", MAX - 1));
+}